@@ -1,7 +1,61 @@
-use iced::Point;
+use iced::{Point, Vector};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Action {
     Tag(usize),
-    Move(Point)
-}
\ No newline at end of file
+    /// Head for the point, which must be within the player's speed; under the holonomic motion model, they get there.
+    Move(Point),
+    /// Move to the point, then tag the given player from there, all in the same turn.
+    MoveAndTag(Point, usize),
+    /// Ask to move at the given velocity, which the motion model turns into actual movement.
+    Steer(Vector),
+    /// Steer at the given velocity, which may be up to the player's sprint speed, spending stamina on going faster than their speed.
+    Sprint(Vector),
+    /// Pick up the given team's flag in capture the flag, which must be within the player's reach; they stand still to do it.
+    PickUp(usize),
+    /// Put down the flag being carried, where the player stands.
+    Drop,
+}
+
+/// Why the environment refused to apply an agent's action.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rejection {
+    /// The acting agent isn't in the environment.
+    UnknownAgent(usize),
+    /// The agent to be tagged isn't in the environment.
+    UnknownTarget(usize),
+    /// A move further than the agent's speed allows.
+    TooFar { distance: f32, speed: f32 },
+    /// A move to a point off the field.
+    OutOfBounds(Point),
+    /// A tag by an agent that isn't 'it'.
+    NotIt,
+    /// A tag on the tagger itself.
+    SelfTag,
+    /// A tag on an agent that is already 'it'.
+    AlreadyIt(usize),
+    /// A tag back on the agent that last tagged the tagger, before the cooldown is up.
+    TagBack(usize),
+    /// A tag on an agent that has only just passed 'it' on.
+    Immune(usize),
+    /// There's an obstacle between the tagger and their target.
+    OutOfSight(usize),
+    /// A tag on a player who's already frozen.
+    Frozen(usize),
+    /// A move or tag by a player who's frozen in place.
+    Stuck,
+    /// A tag on a player on the tagger's own team.
+    Teammate(usize),
+    /// A tag on a player on their own side of the field in team tag.
+    Protected(usize),
+    /// A pick-up of a flag that isn't in the game.
+    UnknownFlag(usize),
+    /// A pick-up of the player's own team's flag.
+    OwnFlag(usize),
+    /// A pick-up of a flag someone is already carrying.
+    FlagTaken(usize),
+    /// A drop by a player who isn't carrying a flag.
+    NotCarrying,
+    /// A tag on an agent further away than the tagger's reach, or a pick-up of a flag out of the player's reach.
+    OutOfReach { distance: f32, reach: f32 },
+}
@@ -1,9 +1,16 @@
 use crate::tag_environment::TagEnvironment;
 use crate::action::{Action};
-use rand::{thread_rng, Rng};
-use iced::Point;
+use rand::Rng;
+use rand::rngs::StdRng;
+use iced::{Point, Vector};
 use crate::parameters::TagParams;
 use std::fmt::Debug;
+use crate::arena::Arena;
+use crate::motion::length;
+use crate::game_mode::{TEAMS, home_side};
+
+/// How many random spots are tried when placing a player clear of the obstacles.
+const SPAWN_ATTEMPTS: usize = 100;
 
 /// A simplistic agent for playing tag.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -14,16 +21,37 @@ pub struct Player {
     pub position: Point,
     pub speed: f32,
     pub reach: f32,
+    /// The radius of the player's body, which other players can't overlap.
+    pub radius: f32,
+    /// How far and which way the player moved last step.
+    pub velocity: Vector,
+    /// The direction the player is facing, in radians.
+    pub heading: f32,
+    /// The maximum distance the player can cover in a sprinting step.
+    pub sprint_speed: f32,
+    /// What's left of the player's stamina, which sprinting uses up.
+    pub stamina: f32,
+    pub max_stamina: f32,
+    /// The player's team in team tag; everyone is on team 0 otherwise.
+    pub team: usize,
 }
 
-pub trait Agent: Sized + Debug + Copy + Clone + PartialEq {
+pub trait Agent: Sized + Debug + Copy + Clone + PartialEq + Send + Sync {
     
-    fn act(&self, env: &TagEnvironment<Self>) -> Action;
+    fn act(&self, env: &TagEnvironment<Self>, rng: &mut StdRng) -> Action;
 
-    fn create(id: usize, params: TagParams) -> Self;
+    fn create(id: usize, params: &TagParams, rng: &mut StdRng) -> Self;
 
     fn update(&mut self, position: &Point);
 
+    fn update_motion(&mut self, velocity: Vector, heading: f32);
+
+    fn update_stamina(&mut self, stamina: f32);
+
+    fn update_attributes(&mut self, speed: f32, reach: f32);
+
+    fn update_team(&mut self, team: usize);
+
     fn player(&self) -> Player;
 
     fn tag(&mut self, by: usize);
@@ -36,32 +64,46 @@ impl Agent for Player {
 
     /// Action selection for the default player agent.
     /// This is overly simplistic -- it just tags any eligible players if 'it' and others are nearby,
-    /// and moves in a random direction otherwise.
-    fn act(&self, env: &TagEnvironment<Player>) -> Action {
-        if self.is_it {
-            for agent in &env.agents {
-                if self.can_tag(*agent) &&
-                    self.distance(*agent) <= self.reach {
-                    return Action::Tag(agent.id)
-                }
-            }
-        }
-
-        self.random_move(env.width, env.height)
+    /// moving up to them first if needed, and moves in a random direction otherwise.
+    /// The lowest eligible id is tagged so that the choice doesn't depend on map iteration order.
+    fn act(&self, env: &TagEnvironment<Player>, rng: &mut StdRng) -> Action {
+        self.choose(env, rng)
     }
 
-    fn create(id: usize, params: TagParams) -> Self {
-        let mut rng = thread_rng();
+    /// Creates a player somewhere random on the field, clear of any obstacles
+    /// (unless a clear spot can't be found, in which case they'll have to find their way out).
+    /// In team games, players alternate between the teams by id, and start on their own side.
+    fn create(id: usize, params: &TagParams, rng: &mut StdRng) -> Self {
+        let team = if params.mode.has_teams() { id % TEAMS } else { 0 };
+        let (left, right) = if params.mode.has_teams() { home_side(team, params.width as f32) } else { (0., params.width as f32) };
+        let mut position = Point::new(0., 0.);
+        for _ in 0..SPAWN_ATTEMPTS {
+            position = Point {
+                x: rng.gen_range(left, right),
+                y: rng.gen_range(0.0, params.height as f32),
+            };
+            if !params.obstacles.iter().any(|obstacle| obstacle.contains(position)) {
+                break;
+            }
+        }
+        let (speed, reach) = match params.roster.get(&id) {
+            Some(attributes) => (attributes.speed, attributes.reach),
+            None => (params.speed.sample(id, rng), params.proximity.sample(id, rng)),
+        };
         Player {
             id,
             is_it: false,
             last_tagged: id,
-            position: Point {
-                x: rng.gen_range(0.0, params.width as f32),
-                y: rng.gen_range(0.0, params.height as f32),
-            },
-            speed: params.speed as f32,
-            reach: params.proximity as f32,
+            position,
+            speed: speed as f32,
+            reach: reach as f32,
+            radius: params.body_radius as f32,
+            velocity: Vector::new(0., 0.),
+            heading: 0.,
+            sprint_speed: params.sprint_speed as f32,
+            stamina: params.stamina as f32,
+            max_stamina: params.stamina as f32,
+            team,
         }
     }
 
@@ -70,6 +112,24 @@ impl Agent for Player {
         self.position = *position;
     }
 
+    fn update_motion(&mut self, velocity: Vector, heading: f32) {
+        self.velocity = velocity;
+        self.heading = heading;
+    }
+
+    fn update_stamina(&mut self, stamina: f32) {
+        self.stamina = stamina;
+    }
+
+    fn update_attributes(&mut self, speed: f32, reach: f32) {
+        self.speed = speed;
+        self.reach = reach;
+    }
+
+    fn update_team(&mut self, team: usize) {
+        self.team = team;
+    }
+
     fn player(&self) -> Player {
         *self
     }
@@ -85,72 +145,131 @@ impl Agent for Player {
 }
 
 impl Player {
-    /// Cartesian distance between two agents.
-    pub fn distance(&self, other: Player) -> f32 {
-        return ((self.position.x - other.position.x).abs().powf(2.) + (self.position.y - other.position.y).abs().powf(2.)).sqrt()
+    /// Cartesian distance between two agents, taking the short way round on a wrapping field.
+    pub fn distance(&self, other: Player, arena: &Arena) -> f32 {
+        arena.distance(self.position, other.position)
     }
 
-    pub fn can_tag(&self, other: Player) -> bool {
-        !other.is_it && other.id != self.id && other.id != self.last_tagged
+    /// The point `distance` along the shortest line to `point`, or `point` itself if that's closer.
+    pub fn step_towards(&self, point: Point, distance: f32, arena: &Arena) -> Point {
+        let [delta_x, delta_y] = arena.delta(self.position, point);
+        let total = (delta_x * delta_x + delta_y * delta_y).sqrt();
+        if total <= distance {
+            return point
+        }
+        let fraction = distance / total;
+        arena.confine(Point {
+            x: self.position.x + delta_x * fraction,
+            y: self.position.y + delta_y * fraction,
+        })
     }
 
     // TODO this and move_away are messy/repetitive and need to be cleaned up
-    pub fn move_towards(&self, other: Player, max_width: f32, max_height: f32) -> Action {
+    pub fn move_towards(&self, other: Player, arena: &Arena, rng: &mut StdRng) -> Action {
         log::debug!("{:?} is moving towards {:?}", self.id, other.id);
-        let delta = self.delta(other);
+        let delta = self.delta(other, arena, rng);
         let direction: f32 = (delta[1] / delta[0]).atan();
         let x = if delta[0] < 0. { self.position.x - (self.speed * direction.cos()).abs() } else { self.position.x + (self.speed * direction.cos()).abs() };
         let y = if delta[1] < 0. { self.position.y - (self.speed * direction.sin()).abs() } else { self.position.y + (self.speed * direction.sin()).abs() };
-        Action::Move(
-            Point {
-                x: Player::clip(x, max_width),
-                y: Player::clip(y, max_height)
-            }
-        )
+        Action::Move(arena.confine(Point { x, y }))
     }
 
     /// Moves directly opposite to the line of sight between the player and the player to move away from.
     /// Might be a good idea to allow more variance in the angle to move at so that it stops running to corners immediately,
-    ///     but this is also less of an issue with higher numbers of players and 'it's.
-    pub fn move_away(&self, other: Player, max_width: f32, max_height: f32) -> Action {
+    ///     but this is also less of an issue with higher numbers of players and 'it's, or with a wrapping or reflecting boundary.
+    pub fn move_away(&self, other: Player, arena: &Arena, rng: &mut StdRng) -> Action {
         log::debug!("{:?} is moving away from  {:?}", self.id, other.id);
-        let delta = self.delta(other);
+        let delta = self.delta(other, arena, rng);
         let direction: f32 = (-delta[1] / -delta[0]).atan();
         let x = if delta[0] < 0. { self.position.x + (self.speed * direction.cos()).abs() } else { self.position.x - (self.speed * direction.cos()).abs() };
         let y =  if delta[1] < 0. { self.position.y + (self.speed * direction.sin()).abs() } else { self.position.y - (self.speed * direction.sin()).abs() };
-        Action::Move(
-            Point {
-                x: Player::clip(x, max_width),
-                y: Player::clip(y, max_height)
-            }
-        )
+        Action::Move(arena.confine(Point { x, y }))
     }
 
     /// Create and return a move action in a random direction.
-    pub fn random_move(&self, max_width: f32, max_height: f32) -> Action {
-        let t: f32 = thread_rng().gen::<f32>() * std::f32::consts::PI * 2.0;
-        let u: f32 = thread_rng().gen::<f32>() + thread_rng().gen::<f32>();
+    pub fn random_move(&self, arena: &Arena, rng: &mut StdRng) -> Action {
+        let t: f32 = rng.gen::<f32>() * std::f32::consts::PI * 2.0;
+        let u: f32 = rng.gen::<f32>() + rng.gen::<f32>();
         let r = if u > 1.0 { 1.0 - u } else { u };
         let x = r * t.cos() * self.speed;
         let y = r * t.sin() * self.speed;
-        Action::Move(Point {
-            x: f32::max(0.0, f32::min(max_width, self.position.x + x)),
-            y: f32::max(0.0, f32::min(max_height, self.position.y + y)),
-        })
+        Action::Move(arena.confine(Point {
+            x: self.position.x + x,
+            y: self.position.y + y,
+        }))
      }
 
-    fn clip(value: f32, max_value: f32) -> f32 {
-        f32::max(0.0, f32::min(max_value, value))
+    /// The default strategy, which plays in any environment so that it can share the field with other strategies.
+    /// Frozen players stand still, in team games everyone tags like an 'it', and in capture the flag
+    /// a player picks up the other team's flag whenever it's in reach.
+    pub fn choose<P: Agent>(&self, env: &TagEnvironment<P>, rng: &mut StdRng) -> Action {
+        if env.frozen.contains(&self.id) {
+            return Action::Steer(Vector::new(0., 0.));
+        }
+        if let Some(flag) = env.flags.iter().find(|flag| env.validate(self.id, &Action::PickUp(flag.team)).is_ok()) {
+            return Action::PickUp(flag.team);
+        }
+        if self.is_it || env.mode.has_teams() {
+            let targets: Vec<Player> = env.neighbours_within(self.position, env.max_tag_range(*self) + self.speed).into_iter()
+                .map(|agent| agent.player())
+                .filter(|agent| env.can_tag(*self, *agent) && self.distance(*agent, &env.arena) <= env.tag_range(*self, *agent) + self.speed)
+                .collect();
+            if let Some(agent) = targets.iter().find(|agent| env.within_reach(*self, self.position, **agent)) {
+                return Action::Tag(agent.id)
+            }
+            for agent in &targets {
+                let position = self.step_towards(agent.position, self.speed, &env.arena);
+                if env.destination(*self, position).map_or(false, |from| env.within_reach(*self, from, *agent)) {
+                    return Action::MoveAndTag(position, agent.id)
+                }
+            }
+        }
+
+        self.avoiding(self.random_move(&env.arena, rng), env)
     }
 
-    fn delta(&self, other: Player) -> [f32; 2] {
-        let mut delta_x: f32 = other.position.x - self.position.x;
-        let mut delta_y: f32 = other.position.y - self.position.y;
+    /// Turns a move into a steering input for the motion model, aside from any obstacle in the way.
+    pub fn avoiding<P: Agent>(&self, action: Action, env: &TagEnvironment<P>) -> Action {
+        match action {
+            Action::Move(position) => {
+                let [delta_x, delta_y] = env.arena.delta(self.position, env.steer(self.position, position));
+                Action::Steer(Vector::new(delta_x, delta_y))
+            }
+            _ => action,
+        }
+    }
+
+    /// Turns a move into a sprint at full pace the same way, kept on the field.
+    /// A player who can't go any faster than their speed just makes the move.
+    pub fn sprinting<P: Agent>(&self, action: Action, env: &TagEnvironment<P>) -> Action {
+        if env.sprint_speed(*self) <= self.speed {
+            return self.avoiding(action, env);
+        }
+        match action {
+            Action::Move(position) => {
+                let [delta_x, delta_y] = env.arena.delta(self.position, position);
+                let distance = length(Vector::new(delta_x, delta_y));
+                if distance == 0. {
+                    return self.avoiding(action, env);
+                }
+                let scale = env.sprint_speed(*self) / distance;
+                let position = env.arena.confine(Point::new(self.position.x + delta_x * scale, self.position.y + delta_y * scale));
+                match self.avoiding(Action::Move(position), env) {
+                    Action::Steer(velocity) => Action::Sprint(velocity),
+                    action => action,
+                }
+            }
+            _ => action,
+        }
+    }
+
+    fn delta(&self, other: Player, arena: &Arena, rng: &mut StdRng) -> [f32; 2] {
+        let [mut delta_x, mut delta_y] = arena.delta(self.position, other.position);
         if delta_y == 0. {
-            delta_y = thread_rng().gen_range(0.1, 1.0);
+            delta_y = rng.gen_range(0.1, 1.0);
         }
         if delta_x == 0. {
-            delta_x = thread_rng().gen_range(0.1, 1.0);
+            delta_x = rng.gen_range(0.1, 1.0);
         }
         [delta_x, delta_y]
     }
@@ -161,23 +280,25 @@ impl Player {
 mod tests {
 
     use crate::tag_environment::TagEnvironment;
-    use dashmap::DashMap;
     use crate::action::{Action};
-    use crate::action::Action::{Tag, Move};
-    use iced::Point;
+    use crate::action::Action::{Tag, Move, MoveAndTag, Steer, Sprint, PickUp, Drop};
+    use iced::{Point, Vector};
     use crate::agents::agent::{Player, Agent};
-    use crate::parameters::DEFAULT_PARAMS;
-    use std::collections::HashSet;
+    use crate::parameters::{DEFAULT_PARAMS, TagParams};
+    use crate::environment::Environment;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::arena::{Arena, Boundary};
 
     #[test]
     fn no_tag_backs() {
-        let env: TagEnvironment<Player> = TagEnvironment {
-            agents: DashMap::with_capacity(2),
-            width: 2.,
-            height: 2.,
-            it: HashSet::new(),
-            show_numbers: false
-        };
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = TagEnvironment::new(&TagParams {
+            width: 2,
+            height: 2,
+            num_players: 2,
+            ..DEFAULT_PARAMS
+        });
         let mut agent1: Player = Player {
             id: 1,
             is_it: true,
@@ -187,7 +308,14 @@ mod tests {
                 y: 0.0
             },
             speed: 2.0,
-            reach: 2.0
+            reach: 2.0,
+            radius: 0.0,
+            velocity: Vector::new(0., 0.),
+            heading: 0.0,
+            sprint_speed: 2.0,
+            stamina: 0.0,
+            max_stamina: 0.0,
+            team: 0
         };
         let agent2: Player = Player {
             id: 2,
@@ -198,19 +326,79 @@ mod tests {
                 y: 0.0
             },
             speed: 2.0,
-            reach: 2.0
+            reach: 2.0,
+            radius: 0.0,
+            velocity: Vector::new(0., 0.),
+            heading: 0.0,
+            sprint_speed: 2.0,
+            stamina: 0.0,
+            max_stamina: 0.0,
+            team: 0
         };
-        env.agents.insert(1, agent1);
-        env.agents.insert(2, agent2);
-        let tag: Action = agent1.act(&env);
+        env.add_agent(agent1);
+        env.add_agent(agent2);
+        let tag: Action = agent1.act(&env, &mut rng);
         assert_eq!(tag, Tag(2), "Making sure an agent tags another in-range agent when 'it'");
         agent1.last_tagged = 2;
-        let tag: Action = agent1.act(&env);
+        let tag: Action = agent1.act(&env, &mut rng);
         assert_ne!(tag, Tag(2), "Making sure an agent does not tag-back the one that tagged it");
     }
 
+    #[test]
+    fn moves_and_tags() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = TagEnvironment::new(&TagParams {
+            width: 10,
+            height: 10,
+            num_players: 2,
+            ..DEFAULT_PARAMS
+        });
+        let it: Player = Player {
+            id: 1,
+            is_it: true,
+            last_tagged: 1,
+            position: Point {
+                x: 0.0,
+                y: 0.0
+            },
+            speed: 2.0,
+            reach: 2.0,
+            radius: 0.0,
+            velocity: Vector::new(0., 0.),
+            heading: 0.0,
+            sprint_speed: 2.0,
+            stamina: 0.0,
+            max_stamina: 0.0,
+            team: 0
+        };
+        let runner: Player = Player {
+            id: 2,
+            is_it: false,
+            last_tagged: 2,
+            position: Point {
+                x: 3.0,
+                y: 0.0
+            },
+            speed: 2.0,
+            reach: 2.0,
+            radius: 0.0,
+            velocity: Vector::new(0., 0.),
+            heading: 0.0,
+            sprint_speed: 2.0,
+            stamina: 0.0,
+            max_stamina: 0.0,
+            team: 0
+        };
+        env.add_agent(it);
+        env.add_agent(runner);
+        assert_eq!(MoveAndTag(Point { x: 2.0, y: 0.0 }, 2), it.act(&env, &mut rng));
+        assert_eq!(Point { x: 1.0, y: 0.0 }, it.step_towards(runner.position, 1.0, &env.arena));
+        assert_eq!(runner.position, it.step_towards(runner.position, 5.0, &env.arena));
+    }
+
     #[test]
     pub fn move_towards() {
+        let mut rng = StdRng::seed_from_u64(0);
         let mut tagged = Player {
             id: 1,
             is_it: true,
@@ -220,7 +408,14 @@ mod tests {
                 y: 0.0
             },
             speed: 1.0,
-            reach: 1.0
+            reach: 1.0,
+            radius: 0.0,
+            velocity: Vector::new(0., 0.),
+            heading: 0.0,
+            sprint_speed: 1.0,
+            stamina: 0.0,
+            max_stamina: 0.0,
+            team: 0
         };
         let mut untagged = Player {
             id: 2,
@@ -231,10 +426,17 @@ mod tests {
                 y: 2.0
             },
             speed: 1.0,
-            reach: 1.0
+            reach: 1.0,
+            radius: 0.0,
+            velocity: Vector::new(0., 0.),
+            heading: 0.0,
+            sprint_speed: 1.0,
+            stamina: 0.0,
+            max_stamina: 0.0,
+            team: 0
         };
         
-        let mut action: Action = tagged.move_towards(untagged, 3., 3.);
+        let mut action: Action = tagged.move_towards(untagged, &Arena::new(3., 3., Boundary::Clamp), &mut rng);
         let mut expected: Action = Move(Point {
             x: std::f32::consts::FRAC_1_SQRT_2,
             y: std::f32::consts::FRAC_1_SQRT_2
@@ -242,26 +444,26 @@ mod tests {
         assert_eq!(expected, action);
         tagged.is_it = false;
         untagged.is_it = true;
-        action = untagged.move_towards(tagged, 3., 3.);
+        action = untagged.move_towards(tagged, &Arena::new(3., 3., Boundary::Clamp), &mut rng);
         expected = Move(Point {
             x: 2. - std::f32::consts::FRAC_1_SQRT_2,
             y: 2. - std::f32::consts::FRAC_1_SQRT_2
         });
         assert_eq!(expected, action);
 
-        tagged = Player::create(1, DEFAULT_PARAMS);
-        tagged.position.x += DEFAULT_PARAMS.speed as f32;
-        tagged.position.y += DEFAULT_PARAMS.speed as f32;
-        untagged = Player::create(2, DEFAULT_PARAMS);
-        untagged.position.x += DEFAULT_PARAMS.speed as f32;
-        untagged.position.y += DEFAULT_PARAMS.speed as f32;
-        let original_dist = tagged.distance(untagged);
-        action = untagged.move_towards(tagged, 2000., 2000.);
+        tagged = Player::create(1, &DEFAULT_PARAMS, &mut rng);
+        tagged.position.x += DEFAULT_PARAMS.speed.mean() as f32;
+        tagged.position.y += DEFAULT_PARAMS.speed.mean() as f32;
+        untagged = Player::create(2, &DEFAULT_PARAMS, &mut rng);
+        untagged.position.x += DEFAULT_PARAMS.speed.mean() as f32;
+        untagged.position.y += DEFAULT_PARAMS.speed.mean() as f32;
+        let original_dist = tagged.distance(untagged, &Arena::new(2000., 2000., Boundary::Clamp));
+        action = untagged.move_towards(tagged, &Arena::new(2000., 2000., Boundary::Clamp), &mut rng);
         match action {
-            Tag(_) => {}
+            Tag(_) | MoveAndTag(_, _) | Steer(_) | Sprint(_) | PickUp(_) | Drop => {}
             Move(point) => {
                 let new_dist = tagged.position.distance(point);
-                assert!((original_dist - DEFAULT_PARAMS.speed as f32 - new_dist).abs() < 0.01);
+                assert!((original_dist - DEFAULT_PARAMS.speed.mean() as f32 - new_dist).abs() < 0.01);
             }
         }
     }
@@ -277,7 +479,14 @@ mod tests {
                 y: 0.0
             },
             speed: 1.0,
-            reach: 1.0
+            reach: 1.0,
+            radius: 0.0,
+            velocity: Vector::new(0., 0.),
+            heading: 0.0,
+            sprint_speed: 1.0,
+            stamina: 0.0,
+            max_stamina: 0.0,
+            team: 0
         };
         let two = Player {
             id: 2,
@@ -288,7 +497,14 @@ mod tests {
                 y: 0.1
             },
             speed: 1.0,
-            reach: 1.0
+            reach: 1.0,
+            radius: 0.0,
+            velocity: Vector::new(0., 0.),
+            heading: 0.0,
+            sprint_speed: 1.0,
+            stamina: 0.0,
+            max_stamina: 0.0,
+            team: 0
         };
         let three = Player {
             id: 3,
@@ -299,18 +515,31 @@ mod tests {
                 y: 0.0
             },
             speed: 1.0,
-            reach: 1.0
+            reach: 1.0,
+            radius: 0.0,
+            velocity: Vector::new(0., 0.),
+            heading: 0.0,
+            sprint_speed: 1.0,
+            stamina: 0.0,
+            max_stamina: 0.0,
+            team: 0
         };
-        let mut dist = three.distance(one);
+        let clamped = Arena::new(1., 1., Boundary::Clamp);
+        let mut dist = three.distance(one, &clamped);
         assert_eq!(0.0, dist);
-        dist = two.distance(one);
+        dist = two.distance(one, &clamped);
         assert!(dist < 1.0);
-        dist = one.distance(two);
+        dist = one.distance(two, &clamped);
         assert!(dist < 1.0);
+        let wrapped = Arena::new(1., 1., Boundary::Wrap);
+        let four = Player { position: Point { x: 0.9, y: 0.0 }, ..one };
+        assert!((one.distance(four, &clamped) - 0.9).abs() < 1e-6);
+        assert!((one.distance(four, &wrapped) - 0.1).abs() < 1e-6);
     }
 
     #[test]
     pub fn move_away() {
+        let mut rng = StdRng::seed_from_u64(0);
         let mut tagged = Player {
             id: 1,
             is_it: true,
@@ -320,7 +549,14 @@ mod tests {
                 y: 0.0
             },
             speed: 1.0,
-            reach: 1.0
+            reach: 1.0,
+            radius: 0.0,
+            velocity: Vector::new(0., 0.),
+            heading: 0.0,
+            sprint_speed: 1.0,
+            stamina: 0.0,
+            max_stamina: 0.0,
+            team: 0
         };
         let mut untagged = Player {
             id: 2,
@@ -331,10 +567,17 @@ mod tests {
                 y: 2.0
             },
             speed: 1.0,
-            reach: 1.0
+            reach: 1.0,
+            radius: 0.0,
+            velocity: Vector::new(0., 0.),
+            heading: 0.0,
+            sprint_speed: 1.0,
+            stamina: 0.0,
+            max_stamina: 0.0,
+            team: 0
         };
 
-        let mut action: Action = untagged.move_away(tagged, 3., 3.);
+        let mut action: Action = untagged.move_away(tagged, &Arena::new(3., 3., Boundary::Clamp), &mut rng);
         let mut expected: Action = Move(Point {
             x: std::f32::consts::FRAC_1_SQRT_2 + 2.,
             y: std::f32::consts::FRAC_1_SQRT_2 + 2.
@@ -342,7 +585,7 @@ mod tests {
         assert_eq!(expected, action);
         tagged.is_it = false;
         untagged.is_it = true;
-        action = tagged.move_away(untagged, 1000., 600.);
+        action = tagged.move_away(untagged, &Arena::new(1000., 600., Boundary::Clamp), &mut rng);
         expected = Move(Point {
             x: 0.,
             y: 0.
@@ -352,26 +595,26 @@ mod tests {
             x: 1.,
             y: 1.
         };
-        action = tagged.move_away(untagged, 3., 3.);
+        action = tagged.move_away(untagged, &Arena::new(3., 3., Boundary::Clamp), &mut rng);
         expected = Move(Point {
             x: 1. - std::f32::consts::FRAC_1_SQRT_2,
             y: 1. - std::f32::consts::FRAC_1_SQRT_2
         });
         assert_eq!(expected, action);
 
-        tagged = Player::create(1, DEFAULT_PARAMS);
-        tagged.position.x += DEFAULT_PARAMS.speed as f32;
-        tagged.position.y += DEFAULT_PARAMS.speed as f32;
-        untagged = Player::create(2, DEFAULT_PARAMS);
-        untagged.position.x += DEFAULT_PARAMS.speed as f32;
-        untagged.position.y += DEFAULT_PARAMS.speed as f32;
-        let original_dist = tagged.distance(untagged);
-        action = untagged.move_away(tagged, 2000., 2000.);
+        tagged = Player::create(1, &DEFAULT_PARAMS, &mut rng);
+        tagged.position.x += DEFAULT_PARAMS.speed.mean() as f32;
+        tagged.position.y += DEFAULT_PARAMS.speed.mean() as f32;
+        untagged = Player::create(2, &DEFAULT_PARAMS, &mut rng);
+        untagged.position.x += DEFAULT_PARAMS.speed.mean() as f32;
+        untagged.position.y += DEFAULT_PARAMS.speed.mean() as f32;
+        let original_dist = tagged.distance(untagged, &Arena::new(2000., 2000., Boundary::Clamp));
+        action = untagged.move_away(tagged, &Arena::new(2000., 2000., Boundary::Clamp), &mut rng);
         match action {
-            Tag(_) => {}
+            Tag(_) | MoveAndTag(_, _) | Steer(_) | Sprint(_) | PickUp(_) | Drop => {}
             Move(point) => {
                 let new_dist = tagged.position.distance(point);
-                assert!((original_dist + DEFAULT_PARAMS.speed as f32 - new_dist).abs() < 0.01);
+                assert!((original_dist + DEFAULT_PARAMS.speed.mean() as f32 - new_dist).abs() < 0.01);
             }
         }
 
@@ -383,7 +626,7 @@ mod tests {
             x: 0.,
             y: 0.
         };
-        action = tagged.move_away(untagged, 1000., 600.);
+        action = tagged.move_away(untagged, &Arena::new(1000., 600., Boundary::Clamp), &mut rng);
         expected = Move(Point {
             x: 0.,
             y: 0.
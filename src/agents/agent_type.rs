@@ -1,5 +1,24 @@
-#[derive(Clone, Copy)]
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AgentType {
     Default,
     BasicDirectional,
-}
\ No newline at end of file
+    /// Players use the strategies in the population, with several on the field at once.
+    Mixed,
+    /// The custom strategy the simulator is built with, `CustomAgent`, for the players down as `custom` in a mix.
+    Custom,
+}
+
+impl FromStr for AgentType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "default" | "random" => Ok(AgentType::Default),
+            "directional" | "basic_directional" => Ok(AgentType::BasicDirectional),
+            "custom" => Ok(AgentType::Custom),
+            _ => Err(format!("Unknown agent type {:?}.", value)),
+        }
+    }
+}
@@ -2,9 +2,10 @@ use crate::agents::agent::{Agent, Player};
 use crate::action::Action;
 use crate::parameters::TagParams;
 use crate::tag_environment::TagEnvironment;
-use iced_native::Point;
-use rand::{thread_rng, Rng};
-use itertools::Itertools;
+use crate::game_mode::home_side;
+use crate::flag::Flag;
+use iced_native::{Point, Vector};
+use rand::rngs::StdRng;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct DirectionalAgent {
@@ -14,52 +15,15 @@ pub struct DirectionalAgent {
 /// A simple tag strategy to run from 'it's or towards non-'it's.
 impl Agent for DirectionalAgent {
 
-    /// If not 'it', run from the nearest 'it'.
-    /// If 'it', run to the nearest non-tagback not-'it'.
-    fn act(&self, env: &TagEnvironment<Self>) -> Action {
-        if self.player.is_it {
-            let mut players = env.agents.iter();
-            let mut nearest = players.next().unwrap();
-            let mut nearest_distance = if self.player.can_tag(nearest.player) { nearest.player.distance(self.player) } else { f32::MAX };
-            if nearest_distance <= self.player.reach {
-                return self.tag(*nearest, env);
-            }
-            for player in players {
-                if self.player.can_tag(player.player) {
-                    let dist = self.player.distance(player.player);
-                    if dist <= self.player.reach {
-                        return self.tag(*player, env);
-                    }
-                    if dist < nearest_distance {
-                        nearest_distance = dist;
-                        nearest = player;
-                    }
-                }
-            }
-            log::debug!("Moving towards {:?}", nearest.player.id);
-            self.player.move_towards(nearest.player, env.width, env.height)
-        } else {
-            let mut nearest_it = env.it.iter()
-                .filter(|player| env.agents.get(player).unwrap().player.last_tagged != self.player.id)
-                .sorted_by(|a, b| {
-                    self.player.distance(env.agents.get(a).unwrap().player)
-                        .partial_cmp(&self.player.distance(env.agents.get(b).unwrap().player)).unwrap()
-                });
-            match nearest_it.next() {
-                None => {
-                    log::warn!("No 'it' found; making random move.");
-                    self.player.random_move(env.width, env.height)
-                }
-                Some(it) => {
-                    self.player.move_away(env.agents.get(it).unwrap().player, env.width, env.height)
-                }
-            }
-        }
+    /// If not 'it', run from the nearest 'it' that could tag this player.
+    /// If 'it', run to the nearest player the rules allow tagging.
+    fn act(&self, env: &TagEnvironment<Self>, rng: &mut StdRng) -> Action {
+        self.choose(env, rng)
     }
 
-    fn create(id: usize, params: TagParams) -> Self {
+    fn create(id: usize, params: &TagParams, rng: &mut StdRng) -> Self {
         DirectionalAgent {
-            player: Player::create(id, params),
+            player: Player::create(id, params, rng),
         }
     }
 
@@ -67,6 +31,22 @@ impl Agent for DirectionalAgent {
         self.player.update(position)
     }
 
+    fn update_motion(&mut self, velocity: Vector, heading: f32) {
+        self.player.update_motion(velocity, heading)
+    }
+
+    fn update_stamina(&mut self, stamina: f32) {
+        self.player.update_stamina(stamina)
+    }
+
+    fn update_attributes(&mut self, speed: f32, reach: f32) {
+        self.player.update_attributes(speed, reach)
+    }
+
+    fn update_team(&mut self, team: usize) {
+        self.player.update_team(team)
+    }
+
     fn player(&self) -> Player {
         self.player
     }
@@ -83,14 +63,144 @@ impl Agent for DirectionalAgent {
 
 impl DirectionalAgent {
 
-    fn tag(&self, other: Self, env: &TagEnvironment<Self>) -> Action {
-        // Add the possibility of failed tags, mostly because players get caught in a loop of
-        // tagging each other in clusters otherwise -- TODO move and tag in single turn(?), or dealt with on its own if agents in own threads later.
-        return if thread_rng().gen_bool(0.8) {
-            Action::Tag(other.player.id)
+    /// The directional strategy, which plays in any environment so that it can share the field with other strategies.
+    pub fn choose<P: Agent>(&self, env: &TagEnvironment<P>, rng: &mut StdRng) -> Action {
+        if env.frozen.contains(&self.player.id) {
+            Action::Steer(Vector::new(0., 0.))
+        } else if env.mode.has_teams() {
+            self.choose_in_team(env, rng)
+        } else if self.player.is_it {
+            match env.nearest_matching(self.player.position, |other| env.can_tag(self.player, other.player())) {
+                None => {
+                    log::warn!("No player to tag found; making random move.");
+                    self.player.avoiding(self.player.random_move(&env.arena, rng), env)
+                }
+                Some(nearest) => self.chase(nearest.player(), env, rng),
+            }
         } else {
-            log::info!("Tag missed!");
-            self.player.random_move(env.width, env.height)
+            let nearest_it = env.it.iter()
+                .map(|it| env.agents.get(it).unwrap().player())
+                .filter(|it| env.can_tag(*it, self.player))
+                .min_by(|a, b| {
+                    self.player.distance(*a, &env.arena).partial_cmp(&self.player.distance(*b, &env.arena)).unwrap()
+                        .then(a.id.cmp(&b.id))
+                });
+            let threatened = nearest_it.map_or(false, |it| self.within_sprint(it, self.player, env));
+            if !threatened && !env.frozen.is_empty() {
+                if let Some(teammate) = env.nearest_matching(self.player.position, |other| env.can_rescue(self.player, other.player())) {
+                    log::debug!("Going to unfreeze {:?}", teammate.player().id);
+                    return self.tag(teammate.player(), env);
+                }
+            }
+            match nearest_it {
+                None => {
+                    log::warn!("No 'it' found; making random move.");
+                    self.player.avoiding(self.player.random_move(&env.arena, rng), env)
+                }
+                Some(it) => {
+                    let escape = self.player.move_away(it, &env.arena, rng);
+                    if self.within_sprint(it, self.player, env) {
+                        self.player.sprinting(escape, env)
+                    } else {
+                        self.player.avoiding(escape, env)
+                    }
+                }
+            }
+        }
+    }
+
+
+    /// Runs from danger, then chases, then rescues, as a member of a team. In capture the flag, a player carrying
+    /// the other team's flag makes for home before anything else, and one with nothing else to do goes for the flag.
+    fn choose_in_team<P: Agent>(&self, env: &TagEnvironment<P>, rng: &mut StdRng) -> Action {
+        if env.carrying(self.player.id).is_some() {
+            if self.cornered(env) {
+                log::debug!("Cornered, dropping the flag");
+                return Action::Drop;
+            }
+            return self.head_home(env);
+        }
+        let threat = env.nearest_matching(self.player.position, |other| env.can_tag(other.player(), self.player))
+            .map(|other| other.player())
+            .filter(|other| self.within_sprint(*other, self.player, env));
+        if let Some(threat) = threat {
+            return self.player.sprinting(self.player.move_away(threat, &env.arena, rng), env);
+        }
+        if let Some(target) = env.nearest_matching(self.player.position, |other| env.can_tag(self.player, other.player())) {
+            return self.chase(target.player(), env, rng);
+        }
+        if let Some(teammate) = env.nearest_matching(self.player.position, |other| env.can_rescue(self.player, other.player())) {
+            log::debug!("Going to free {:?}", teammate.player().id);
+            return self.tag(teammate.player(), env);
+        }
+        if let Some(flag) = env.flags.iter().find(|flag| flag.team != self.player.team && flag.carrier.is_none()) {
+            return self.fetch(flag, env);
+        }
+        self.player.avoiding(self.player.random_move(&env.arena, rng), env)
+    }
+
+    /// Sprints straight across the line onto the player's own side of the field.
+    fn head_home<P: Agent>(&self, env: &TagEnvironment<P>) -> Action {
+        let (left, right) = home_side(self.player.team, env.arena.width);
+        let x = if self.player.position.x < left { left + self.player.speed } else { right - self.player.speed };
+        let home = Point::new(x, self.player.position.y);
+        log::debug!("Carrying the flag home");
+        self.player.sprinting(Action::Move(self.player.step_towards(home, self.player.speed, &env.arena)), env)
+    }
+
+    /// Whether someone about to catch the player stands between them and their own side,
+    /// in which case a carrier puts the flag down for a teammate rather than running into them with it.
+    fn cornered<P: Agent>(&self, env: &TagEnvironment<P>) -> bool {
+        let (left, right) = home_side(self.player.team, env.arena.width);
+        let line = if self.player.position.x < left { left } else { right };
+        env.nearest_matching(self.player.position, |other| env.can_tag(other.player(), self.player))
+            .map(|other| other.player())
+            .filter(|threat| self.within_sprint(*threat, self.player, env))
+            .filter(|threat| (threat.position.x - line).abs() < (self.player.position.x - line).abs())
+            .is_some()
+    }
+
+    /// Picks up the flag if it's in reach, or heads for it.
+    fn fetch<P: Agent>(&self, flag: &Flag, env: &TagEnvironment<P>) -> Action {
+        if env.arena.distance(self.player.position, flag.position) <= self.player.reach {
+            return Action::PickUp(flag.team);
+        }
+        log::debug!("Going for team {:?}'s flag", flag.team);
+        self.player.avoiding(Action::Move(self.player.step_towards(flag.position, self.player.speed, &env.arena)), env)
+    }
+
+    /// Goes after `target`, tagging them if they're close enough, and sprinting once they're nearly in reach
+    /// or whenever stamina is full, since it can't recover any further.
+    fn chase<P: Agent>(&self, target: Player, env: &TagEnvironment<P>, rng: &mut StdRng) -> Action {
+        if self.player.distance(target, &env.arena) <= env.tag_range(self.player, target) + self.player.speed {
+            return self.tag(target, env);
+        }
+        log::debug!("Moving towards {:?}", target.id);
+        let chase = self.player.move_towards(target, &env.arena, rng);
+        let rested = env.stamina(self.player.id).map_or(false, |(stamina, most)| stamina >= most);
+        if rested || self.within_sprint(self.player, target, env) {
+            self.player.sprinting(chase, env)
+        } else {
+            self.player.avoiding(chase, env)
+        }
+    }
+
+    /// Whether `tagger` could get within reach of `target` in a couple of sprinting steps.
+    fn within_sprint<P: Agent>(&self, tagger: Player, target: Player, env: &TagEnvironment<P>) -> bool {
+        tagger.distance(target, &env.arena) <= env.tag_range(tagger, target) + 2. * env.sprint_speed(tagger)
+    }
+
+    /// Tags the other player, first moving up to them if they're out of reach; a runner tags a frozen teammate to free them.
+    /// If an obstacle is in the way, it heads around it instead.
+    fn tag<P: Agent>(&self, other: Player, env: &TagEnvironment<P>) -> Action {
+        if env.within_reach(self.player, self.player.position, other) {
+            return Action::Tag(other.id);
+        }
+        let position = self.player.step_towards(other.position, self.player.speed, &env.arena);
+        if env.destination(self.player, position).map_or(false, |from| env.within_reach(self.player, from, other)) {
+            Action::MoveAndTag(position, other.id)
+        } else {
+            self.player.avoiding(Action::Move(position), env)
         }
     }
 
@@ -100,20 +210,23 @@ impl DirectionalAgent {
 mod tests {
     use crate::tag_environment::TagEnvironment;
     use crate::agents::basic_directional::DirectionalAgent;
-    use dashmap::DashMap;
-    use std::collections::HashSet;
     use crate::agents::agent::{Player, Agent};
-    use iced::Point;
+    use crate::parameters::{TagParams, DEFAULT_PARAMS};
+    use iced::{Point, Vector};
     use crate::environment::Environment;
-    use crate::action::Action::Tag;
+    use crate::action::Action::{self, Tag};
+    use crate::game_mode::GameMode;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
 
     #[test]
     fn tag_zero() {
         let env = base_env();
+        let mut rng = StdRng::seed_from_u64(0);
         let player = env.agents.get(&1).unwrap();
         let mut passed = false;
         for _ in 0..10 {
-            let action = player.act(&env);
+            let action = player.act(&env, &mut rng);
             if action.eq(&Tag(0)) {
                 passed = true;
                 break;
@@ -122,14 +235,32 @@ mod tests {
         assert!(passed);
     }
 
-    fn base_env() -> TagEnvironment<DirectionalAgent> {
-        let mut env: TagEnvironment<DirectionalAgent> = TagEnvironment {
-            agents: DashMap::with_capacity(2),
-            width: 2.,
-            height: 2.,
-            it: HashSet::new(),
-            show_numbers: false
+    #[test]
+    fn cornered_carriers_drop_the_flag() {
+        // a flag carrier at x = 84, heading for their own half below x = 50, with a defender at `defender`
+        let carrier_action = |defender: f32| {
+            let mut rng = StdRng::seed_from_u64(0);
+            let params = TagParams { width: 100, height: 40, num_players: 0, mode: GameMode::Flag, ..DEFAULT_PARAMS };
+            let mut env: TagEnvironment<DirectionalAgent> = TagEnvironment::new(&params);
+            for &(id, team, x) in &[(0, 0, 84.), (1, 1, defender)] {
+                let agent = DirectionalAgent::create(id, &params, &mut rng);
+                env.add_agent(DirectionalAgent { player: Player { position: Point::new(x, 20.), team, speed: 2., reach: 2., ..agent.player } });
+            }
+            env.flags[1].carrier = Some(0);
+            let carrier = *env.agents.get(&0).unwrap();
+            carrier.act(&env, &mut rng)
         };
+        assert_ne!(Action::Drop, carrier_action(88.));
+        assert_eq!(Action::Drop, carrier_action(80.));
+    }
+
+    fn base_env() -> TagEnvironment<DirectionalAgent> {
+        let mut env: TagEnvironment<DirectionalAgent> = TagEnvironment::new(&TagParams {
+            width: 2,
+            height: 2,
+            num_players: 2,
+            ..DEFAULT_PARAMS
+        });
         let agent0: DirectionalAgent = DirectionalAgent {
             player: Player {
                 id: 0,
@@ -140,7 +271,14 @@ mod tests {
                     y: 0.0,
                 },
                 speed: 2.0,
-                reach: 2.0
+                reach: 2.0,
+                radius: 0.0,
+                velocity: Vector::new(0., 0.),
+                heading: 0.0,
+                sprint_speed: 2.0,
+                stamina: 0.0,
+                max_stamina: 0.0,
+                team: 0
             }
         };
         let agent1: DirectionalAgent = DirectionalAgent {
@@ -153,7 +291,14 @@ mod tests {
                     y: 0.5,
                 },
                 speed: 2.0,
-                reach: 2.0
+                reach: 2.0,
+                radius: 0.0,
+                velocity: Vector::new(0., 0.),
+                heading: 0.0,
+                sprint_speed: 2.0,
+                stamina: 0.0,
+                max_stamina: 0.0,
+                team: 0
             }
         };
         env.it.insert(1);
@@ -0,0 +1,123 @@
+use crate::agents::agent::{Agent, Player};
+use crate::agents::mixed::Strategy;
+use crate::action::Action;
+use crate::parameters::TagParams;
+use crate::tag_environment::TagEnvironment;
+use iced_native::{Point, Vector};
+use rand::rngs::StdRng;
+
+/// How many of an 'it's steps away a runner lets them get before running.
+const WARNING_STEPS: f32 = 3.;
+
+/// The custom strategy the simulator is built with, played by the players down as `custom` in a mix.
+/// Change `choose` to try a strategy of your own against the built-in ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CustomAgent {
+    pub player: Player,
+}
+
+impl Agent for CustomAgent {
+
+    fn act(&self, env: &TagEnvironment<Self>, rng: &mut StdRng) -> Action {
+        self.choose(env, rng)
+    }
+
+    fn create(id: usize, params: &TagParams, rng: &mut StdRng) -> Self {
+        CustomAgent {
+            player: Player::create(id, params, rng),
+        }
+    }
+
+    fn update(&mut self, position: &Point) {
+        self.player.update(position)
+    }
+
+    fn update_motion(&mut self, velocity: Vector, heading: f32) {
+        self.player.update_motion(velocity, heading)
+    }
+
+    fn update_stamina(&mut self, stamina: f32) {
+        self.player.update_stamina(stamina)
+    }
+
+    fn update_attributes(&mut self, speed: f32, reach: f32) {
+        self.player.update_attributes(speed, reach)
+    }
+
+    fn update_team(&mut self, team: usize) {
+        self.player.update_team(team)
+    }
+
+    fn player(&self) -> Player {
+        self.player
+    }
+
+    fn tag(&mut self, by: usize) {
+        self.player.tag(by)
+    }
+
+    fn untag(&mut self) {
+        self.player.untag()
+    }
+
+}
+
+impl Strategy for CustomAgent {
+
+    /// Stays put until someone who could tag the player gets within a few steps, then runs straight away from them;
+    /// when able to tag, goes after the nearest player who can be tagged.
+    fn choose<P: Agent>(&self, env: &TagEnvironment<P>, rng: &mut StdRng) -> Action {
+        let me = self.player;
+        if env.frozen.contains(&me.id) {
+            return Action::Steer(Vector::new(0., 0.));
+        }
+        if let Some(target) = env.nearest_matching(me.position, |other| env.can_tag(me, other.player())) {
+            let target = target.player();
+            if env.within_reach(me, me.position, target) {
+                return Action::Tag(target.id);
+            }
+            return me.avoiding(me.move_towards(target, &env.arena, rng), env);
+        }
+        let threat = env.nearest_matching(me.position, |other| env.can_tag(other.player(), me))
+            .map(|other| other.player())
+            .filter(|other| me.distance(*other, &env.arena) <= env.tag_range(*other, me) + WARNING_STEPS * other.speed);
+        match threat {
+            Some(threat) => me.avoiding(me.move_away(threat, &env.arena, rng), env),
+            None => Action::Steer(Vector::new(0., 0.)),
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agents::agent::{Agent, Player};
+    use crate::agents::custom::CustomAgent;
+    use crate::action::Action;
+    use crate::parameters::{TagParams, DEFAULT_PARAMS};
+    use crate::tag_environment::TagEnvironment;
+    use crate::environment::Environment;
+    use iced::{Point, Vector};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn runners_wait_until_an_it_comes_close() {
+        // a runner at x = 50 and an 'it' at x = `it`
+        let runner_action = |it: f32| {
+            let mut rng = StdRng::seed_from_u64(0);
+            let params = TagParams { width: 100, height: 100, num_players: 0, ..DEFAULT_PARAMS };
+            let mut env: TagEnvironment<CustomAgent> = TagEnvironment::new(&params);
+            for &(id, x) in &[(0, 50.), (1, it)] {
+                let agent = CustomAgent::create(id, &params, &mut rng);
+                env.add_agent(CustomAgent { player: Player { position: Point::new(x, 50.), is_it: id == 1, speed: 2., reach: 2., ..agent.player } });
+            }
+            env.it.insert(1);
+            let runner = *env.agents.get(&0).unwrap();
+            runner.act(&env, &mut rng)
+        };
+        assert_eq!(Action::Steer(Vector::new(0., 0.)), runner_action(90.));
+        assert_ne!(Action::Steer(Vector::new(0., 0.)), runner_action(55.));
+    }
+
+}
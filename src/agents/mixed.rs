@@ -0,0 +1,263 @@
+use crate::agents::agent::{Agent, Player};
+use crate::agents::agent_type::AgentType;
+use crate::agents::basic_directional::DirectionalAgent;
+use crate::action::Action;
+use crate::parameters::TagParams;
+use crate::tag_environment::TagEnvironment;
+use iced_native::{Point, Vector};
+use rand::rngs::StdRng;
+use std::str::FromStr;
+
+/// How many players use each strategy, with ids handed out in the order given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Population(pub Vec<(AgentType, usize)>);
+
+impl FromStr for Population {
+    type Err = String;
+
+    /// Parses `strategy:count,strategy:count,...`, e.g. `default:30,directional:20,custom:2`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid population {:?}.", value);
+        value.split(',')
+            .map(|group| {
+                let mut parts = group.splitn(2, ':');
+                let strategy = parts.next().unwrap_or_default().trim().parse::<AgentType>()?;
+                if strategy == AgentType::Mixed {
+                    return Err(invalid());
+                }
+                let count = parts.next().ok_or_else(invalid)?.trim().parse::<usize>().map_err(|_| invalid())?;
+                Ok((strategy, count))
+            })
+            .collect::<Result<Vec<(AgentType, usize)>, String>>()
+            .map(Population)
+    }
+}
+
+impl Population {
+
+    pub fn total(&self) -> usize {
+        self.0.iter().map(|(_, count)| count).sum()
+    }
+
+    /// The strategy of the player with the given id. Anyone past the end of the population, such as a player joining
+    /// mid-game, is counted from the start again, so that the mix keeps its proportions; with no population at all,
+    /// everyone uses the default.
+    pub fn strategy(&self, id: usize) -> AgentType {
+        let total = self.total();
+        if total == 0 {
+            return AgentType::Default;
+        }
+        let id = id % total;
+        let mut end = 0;
+        for (strategy, count) in &self.0 {
+            end += count;
+            if id < end {
+                return *strategy;
+            }
+        }
+        AgentType::Default
+    }
+
+}
+
+/// A strategy that can decide on an action in a game shared with other strategies, whatever agent type the
+/// environment holds, so that it can play as part of a mixed population.
+pub trait Strategy: Agent {
+
+    fn choose<P: Agent>(&self, env: &TagEnvironment<P>, rng: &mut StdRng) -> Action;
+
+}
+
+impl Strategy for Player {
+
+    fn choose<P: Agent>(&self, env: &TagEnvironment<P>, rng: &mut StdRng) -> Action {
+        Player::choose(self, env, rng)
+    }
+
+}
+
+impl Strategy for DirectionalAgent {
+
+    fn choose<P: Agent>(&self, env: &TagEnvironment<P>, rng: &mut StdRng) -> Action {
+        DirectionalAgent::choose(self, env, rng)
+    }
+
+}
+
+/// A player using any one of the built-in strategies, or the custom strategy `C`, so that different strategies
+/// can share the field. A custom strategy joins in by implementing `Strategy`, and is given to the players
+/// down as `custom` in the population.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MixedAgent<C: Strategy = Player> {
+    Default(Player),
+    Directional(DirectionalAgent),
+    Custom(C),
+}
+
+impl<C: Strategy> Agent for MixedAgent<C> {
+
+    fn act(&self, env: &TagEnvironment<Self>, rng: &mut StdRng) -> Action {
+        match self {
+            MixedAgent::Default(player) => player.choose(env, rng),
+            MixedAgent::Directional(agent) => agent.choose(env, rng),
+            MixedAgent::Custom(agent) => agent.choose(env, rng),
+        }
+    }
+
+    fn create(id: usize, params: &TagParams, rng: &mut StdRng) -> Self {
+        match params.population.strategy(id) {
+            AgentType::Default | AgentType::Mixed => MixedAgent::Default(Player::create(id, params, rng)),
+            AgentType::BasicDirectional => MixedAgent::Directional(DirectionalAgent::create(id, params, rng)),
+            AgentType::Custom => MixedAgent::Custom(C::create(id, params, rng)),
+        }
+    }
+
+    fn update(&mut self, position: &Point) {
+        match self {
+            MixedAgent::Default(player) => player.update(position),
+            MixedAgent::Directional(agent) => agent.update(position),
+            MixedAgent::Custom(agent) => agent.update(position),
+        }
+    }
+
+    fn update_motion(&mut self, velocity: Vector, heading: f32) {
+        match self {
+            MixedAgent::Default(player) => player.update_motion(velocity, heading),
+            MixedAgent::Directional(agent) => agent.update_motion(velocity, heading),
+            MixedAgent::Custom(agent) => agent.update_motion(velocity, heading),
+        }
+    }
+
+    fn update_stamina(&mut self, stamina: f32) {
+        match self {
+            MixedAgent::Default(player) => player.update_stamina(stamina),
+            MixedAgent::Directional(agent) => agent.update_stamina(stamina),
+            MixedAgent::Custom(agent) => agent.update_stamina(stamina),
+        }
+    }
+
+    fn update_attributes(&mut self, speed: f32, reach: f32) {
+        match self {
+            MixedAgent::Default(player) => player.update_attributes(speed, reach),
+            MixedAgent::Directional(agent) => agent.update_attributes(speed, reach),
+            MixedAgent::Custom(agent) => agent.update_attributes(speed, reach),
+        }
+    }
+
+    fn update_team(&mut self, team: usize) {
+        match self {
+            MixedAgent::Default(player) => player.update_team(team),
+            MixedAgent::Directional(agent) => agent.update_team(team),
+            MixedAgent::Custom(agent) => agent.update_team(team),
+        }
+    }
+
+    fn player(&self) -> Player {
+        match self {
+            MixedAgent::Default(player) => player.player(),
+            MixedAgent::Directional(agent) => agent.player(),
+            MixedAgent::Custom(agent) => agent.player(),
+        }
+    }
+
+    fn tag(&mut self, by: usize) {
+        match self {
+            MixedAgent::Default(player) => player.tag(by),
+            MixedAgent::Directional(agent) => agent.tag(by),
+            MixedAgent::Custom(agent) => agent.tag(by),
+        }
+    }
+
+    fn untag(&mut self) {
+        match self {
+            MixedAgent::Default(player) => player.untag(),
+            MixedAgent::Directional(agent) => agent.untag(),
+            MixedAgent::Custom(agent) => agent.untag(),
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agents::mixed::{Population, MixedAgent, Strategy};
+    use crate::agents::agent_type::AgentType;
+    use crate::agents::agent::{Agent, Player};
+    use crate::parameters::{TagParams, DEFAULT_PARAMS};
+    use crate::simulation::Simulation;
+    use crate::tag_environment::TagEnvironment;
+    use crate::action::Action;
+    use iced::{Point, Vector};
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn parses_populations() {
+        let population: Population = "default:3, directional:2".parse().unwrap();
+        assert_eq!(Population(vec![(AgentType::Default, 3), (AgentType::BasicDirectional, 2)]), population);
+        assert_eq!(5, population.total());
+        assert_eq!(AgentType::Default, population.strategy(2));
+        assert_eq!(AgentType::BasicDirectional, population.strategy(3));
+        assert_eq!(AgentType::Default, population.strategy(5));
+        assert_eq!(AgentType::BasicDirectional, population.strategy(9));
+        assert_eq!(AgentType::Default, Population(Vec::new()).strategy(4));
+        assert!("default:3,chess:2".parse::<Population>().is_err());
+        assert!("default:3,mixed:2".parse::<Population>().is_err());
+        assert!("default".parse::<Population>().is_err());
+    }
+
+    #[test]
+    fn strategies_share_the_field() {
+        let population: Population = "default:30,directional:20".parse().unwrap();
+        let params = TagParams { num_players: population.total(), num_it: 3, agent_type: AgentType::Mixed, population, ..DEFAULT_PARAMS };
+        let mut sim: Simulation<MixedAgent> = Simulation::new(params);
+        sim.run_headless(Option::from(200));
+        assert!(sim.environment.violations.is_empty(), "{:?}", sim.environment.violations);
+        let directional = sim.environment.agents.iter().filter(|agent| matches!(agent.value(), MixedAgent::Directional(_))).count();
+        assert_eq!(20, directional);
+    }
+
+    /// A custom strategy, kept outside the built-in ones: stand still and never tag.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Statue(Player);
+
+    impl Agent for Statue {
+        fn act(&self, env: &TagEnvironment<Self>, rng: &mut StdRng) -> Action { self.choose(env, rng) }
+        fn create(id: usize, params: &TagParams, rng: &mut StdRng) -> Self { Statue(Player::create(id, params, rng)) }
+        fn update(&mut self, position: &Point) { self.0.update(position) }
+        fn update_motion(&mut self, velocity: Vector, heading: f32) { self.0.update_motion(velocity, heading) }
+        fn update_stamina(&mut self, stamina: f32) { self.0.update_stamina(stamina) }
+        fn update_attributes(&mut self, speed: f32, reach: f32) { self.0.update_attributes(speed, reach) }
+        fn update_team(&mut self, team: usize) { self.0.update_team(team) }
+        fn player(&self) -> Player { self.0 }
+        fn tag(&mut self, by: usize) { self.0.tag(by) }
+        fn untag(&mut self) { self.0.untag() }
+    }
+
+    impl Strategy for Statue {
+        fn choose<P: Agent>(&self, _: &TagEnvironment<P>, _: &mut StdRng) -> Action { Action::Steer(Vector::new(0., 0.)) }
+    }
+
+    #[test]
+    fn custom_strategies_join_the_mix() {
+        let population: Population = "default:3,directional:2,custom:2".parse().unwrap();
+        let params = TagParams { num_players: population.total(), agent_type: AgentType::Mixed, population, ..DEFAULT_PARAMS };
+        let mut sim: Simulation<MixedAgent<Statue>> = Simulation::new(params);
+        let custom = |sim: &Simulation<MixedAgent<Statue>>| {
+            let mut statues: Vec<(usize, Point)> = sim.environment.agents.iter()
+                .filter(|agent| matches!(agent.value(), MixedAgent::Custom(_)))
+                .map(|agent| (*agent.key(), agent.player().position))
+                .collect();
+            statues.sort_unstable_by_key(|(id, _)| *id);
+            statues
+        };
+        let start = custom(&sim);
+        assert_eq!(vec![5, 6], start.iter().map(|(id, _)| *id).collect::<Vec<usize>>());
+        sim.run_headless(Option::from(50));
+        assert!(sim.environment.violations.is_empty(), "{:?}", sim.environment.violations);
+        assert_eq!(start, custom(&sim));
+        // a player joining past the end of the population carries on through the mix
+        assert_eq!(7, sim.join());
+        assert!(matches!(*sim.environment.agents.get(&7).unwrap(), MixedAgent::Default(_)));
+    }
+
+}
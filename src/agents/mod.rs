@@ -1,3 +1,5 @@
 pub(crate) mod agent_type;
 pub(crate) mod agent;
 pub(crate) mod basic_directional;
+pub(crate) mod mixed;
+pub(crate) mod custom;
@@ -0,0 +1,191 @@
+use iced::{Point, Vector};
+use std::f32::consts::PI;
+use std::str::FromStr;
+
+/// What happens to a player who moves past the edge of the field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Boundary {
+    /// Players stop at the edges; the environment refuses moves past them.
+    Clamp,
+    /// The field is a torus: leaving one edge brings a player back in at the opposite edge.
+    Wrap,
+    /// Players bounce off the edges, as far back in as they would have gone past them.
+    Reflect,
+    /// Players who leave the field are out of the game.
+    Eliminate,
+}
+
+impl FromStr for Boundary {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "clamp" => Ok(Boundary::Clamp),
+            "wrap" => Ok(Boundary::Wrap),
+            "reflect" => Ok(Boundary::Reflect),
+            "eliminate" => Ok(Boundary::Eliminate),
+            _ => Err(format!("Unknown boundary policy {:?}.", value)),
+        }
+    }
+}
+
+/// The playing field and its boundary policy, which between them decide where players end up and how far apart they are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arena {
+    pub width: f32,
+    pub height: f32,
+    pub boundary: Boundary,
+}
+
+impl Arena {
+
+    pub fn new(width: f32, height: f32, boundary: Boundary) -> Self {
+        Arena {
+            width,
+            height,
+            boundary,
+        }
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        (0.0..=self.width).contains(&point.x) && (0.0..=self.height).contains(&point.y)
+    }
+
+    /// The shortest displacement from `from` to `to`, which on a wrapping field may cross the edges.
+    pub fn delta(&self, from: Point, to: Point) -> [f32; 2] {
+        let delta_x = to.x - from.x;
+        let delta_y = to.y - from.y;
+        match self.boundary {
+            Boundary::Wrap => [Arena::shortest(delta_x, self.width), Arena::shortest(delta_y, self.height)],
+            _ => [delta_x, delta_y],
+        }
+    }
+
+    /// The length of the shortest displacement between two points.
+    pub fn distance(&self, from: Point, to: Point) -> f32 {
+        let [delta_x, delta_y] = self.delta(from, to);
+        (delta_x * delta_x + delta_y * delta_y).sqrt()
+    }
+
+    /// Brings a point onto the field the way the boundary policy would, without ever leaving the game;
+    /// this is how agents keep their moves on the field.
+    pub fn confine(&self, point: Point) -> Point {
+        match self.boundary {
+            Boundary::Clamp | Boundary::Eliminate => Point::new(point.x.max(0.).min(self.width), point.y.max(0.).min(self.height)),
+            Boundary::Wrap => Point::new(Arena::wrap(point.x, self.width), Arena::wrap(point.y, self.height)),
+            Boundary::Reflect => Point::new(Arena::reflect(point.x, self.width), Arena::reflect(point.y, self.height)),
+        }
+    }
+
+    /// Where a player moving to `point` ends up, or `None` if they've left the game.
+    pub fn settle(&self, point: Point) -> Option<Point> {
+        match self.boundary {
+            Boundary::Eliminate if !self.contains(point) => None,
+            _ => Some(self.confine(point)),
+        }
+    }
+
+    /// The velocity and heading a player is left with after moving at `velocity`, facing `heading`, to `point`:
+    /// turned back off each edge they bounced off when reflecting, and stopped against each edge they ran into
+    /// when clamped. Crossing the edges of a wrapping field changes neither.
+    pub fn rebound(&self, point: Point, velocity: Vector, heading: f32) -> (Vector, f32) {
+        match self.boundary {
+            Boundary::Reflect => {
+                let (mut velocity, mut heading) = (velocity, heading);
+                if point.x.rem_euclid(2. * self.width) > self.width {
+                    velocity.x = -velocity.x;
+                    heading = PI - heading;
+                }
+                if point.y.rem_euclid(2. * self.height) > self.height {
+                    velocity.y = -velocity.y;
+                    heading = -heading;
+                }
+                (velocity, heading)
+            }
+            Boundary::Clamp | Boundary::Eliminate => {
+                let x = if (0.0..=self.width).contains(&point.x) { velocity.x } else { 0. };
+                let y = if (0.0..=self.height).contains(&point.y) { velocity.y } else { 0. };
+                (Vector::new(x, y), heading)
+            }
+            Boundary::Wrap => (velocity, heading),
+        }
+    }
+
+    /// The copies of `point` that come within `radius` of the field, starting with `point` itself.
+    /// On a wrapping field these are shifted by the field's size, so that anything near one edge can be found
+    /// (or drawn) near the opposite edge too; otherwise there is only the point itself.
+    pub fn images(&self, point: Point, radius: f32) -> Vec<Point> {
+        self.shifts().into_iter()
+            .map(|[shift_x, shift_y]| Point::new(point.x + shift_x, point.y + shift_y))
+            .filter(|image| self.distance_to_field(*image) <= radius)
+            .collect()
+    }
+
+    /// The offsets at which the field repeats, starting with no offset at all.
+    pub fn shifts(&self) -> Vec<[f32; 2]> {
+        match self.boundary {
+            Boundary::Wrap => {
+                let mut shifts = Vec::with_capacity(9);
+                for shift_x in &[0., -self.width, self.width] {
+                    for shift_y in &[0., -self.height, self.height] {
+                        shifts.push([*shift_x, *shift_y]);
+                    }
+                }
+                shifts
+            }
+            _ => vec![[0., 0.]],
+        }
+    }
+
+    /// How far a point is from the nearest point of the field.
+    pub fn distance_to_field(&self, point: Point) -> f32 {
+        let outside_x = f32::max(0., f32::max(-point.x, point.x - self.width));
+        let outside_y = f32::max(0., f32::max(-point.y, point.y - self.height));
+        (outside_x * outside_x + outside_y * outside_y).sqrt()
+    }
+
+    fn shortest(delta: f32, size: f32) -> f32 {
+        let delta = delta.rem_euclid(size);
+        if delta > size / 2. { delta - size } else { delta }
+    }
+
+    fn wrap(value: f32, size: f32) -> f32 {
+        value.rem_euclid(size)
+    }
+
+    fn reflect(value: f32, size: f32) -> f32 {
+        let folded = value.rem_euclid(2. * size);
+        if folded > size { 2. * size - folded } else { folded }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arena::{Arena, Boundary};
+    use iced::Point;
+
+    #[test]
+    fn settles_by_policy() {
+        let outside = Point::new(12., -1.);
+        assert_eq!(Some(Point::new(10., 0.)), Arena::new(10., 5., Boundary::Clamp).settle(outside));
+        assert_eq!(Some(Point::new(2., 4.)), Arena::new(10., 5., Boundary::Wrap).settle(outside));
+        assert_eq!(Some(Point::new(8., 1.)), Arena::new(10., 5., Boundary::Reflect).settle(outside));
+        assert_eq!(None, Arena::new(10., 5., Boundary::Eliminate).settle(outside));
+        assert_eq!(Some(Point::new(3., 3.)), Arena::new(10., 5., Boundary::Eliminate).settle(Point::new(3., 3.)));
+    }
+
+    #[test]
+    fn measures_across_wrapped_edges() {
+        let wrapped = Arena::new(10., 10., Boundary::Wrap);
+        let clamped = Arena::new(10., 10., Boundary::Clamp);
+        let (left, right) = (Point::new(1., 5.), Point::new(9., 5.));
+        assert_eq!([-2., 0.], wrapped.delta(left, right));
+        assert!((wrapped.distance(left, right) - 2.).abs() < 1e-6);
+        assert!((clamped.distance(left, right) - 8.).abs() < 1e-6);
+        assert_eq!(vec![Point::new(1., 5.), Point::new(11., 5.)], wrapped.images(left, 1.5));
+        assert_eq!(4, wrapped.images(Point::new(0.5, 9.5), 1.).len());
+        assert_eq!(vec![left], clamped.images(left, 1.5));
+    }
+
+}
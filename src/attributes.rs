@@ -0,0 +1,148 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::fs;
+use std::str::FromStr;
+
+/// Where a player attribute, such as speed or reach, is drawn from when players are created.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distribution {
+    /// Every player gets the same value.
+    Fixed(f64),
+    Uniform { low: f64, high: f64 },
+    Normal { mean: f64, deviation: f64 },
+    /// The values are handed out in order of player id, starting again from the first once they run out.
+    List(Vec<f64>),
+}
+
+impl FromStr for Distribution {
+    type Err = String;
+
+    /// Parses a plain number, `uniform:low,high`, `normal:mean,deviation` or `list:a,b,c,...`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid distribution {:?}.", value);
+        if let Ok(fixed) = value.trim().parse::<f64>() {
+            return Ok(Distribution::Fixed(fixed));
+        }
+        let mut parts = value.splitn(2, ':');
+        let kind = parts.next().unwrap_or_default().to_lowercase();
+        let numbers: Vec<f64> = parts.next().ok_or_else(invalid)?
+            .split(',')
+            .map(|number| number.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|_| invalid())?;
+        match kind.as_str() {
+            "uniform" if numbers.len() == 2 && numbers[0] <= numbers[1] => Ok(Distribution::Uniform { low: numbers[0], high: numbers[1] }),
+            "normal" if numbers.len() == 2 && numbers[1] >= 0. => Ok(Distribution::Normal { mean: numbers[0], deviation: numbers[1] }),
+            "list" if !numbers.is_empty() => Ok(Distribution::List(numbers)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Distribution {
+
+    /// Draws the value for player `id`; attributes can't be negative, so negative draws come out as 0.
+    /// Fixed values and lists don't use the random number generator at all.
+    pub fn sample(&self, id: usize, rng: &mut StdRng) -> f64 {
+        let value = match self {
+            Distribution::Fixed(value) => *value,
+            Distribution::Uniform { low, high } => if low < high { rng.gen_range(*low, *high) } else { *low },
+            Distribution::Normal { mean, deviation } => {
+                // Box-Muller transform
+                let u: f64 = 1. - rng.gen::<f64>();
+                let v: f64 = rng.gen();
+                mean + deviation * (-2. * u.ln()).sqrt() * (2. * PI * v).cos()
+            }
+            Distribution::List(values) => values[id % values.len()],
+        };
+        f64::max(0., value)
+    }
+
+    /// The value players get on average.
+    pub fn mean(&self) -> f64 {
+        match self {
+            Distribution::Fixed(value) => *value,
+            Distribution::Uniform { low, high } => (low + high) / 2.,
+            Distribution::Normal { mean, .. } => *mean,
+            Distribution::List(values) => values.iter().sum::<f64>() / values.len() as f64,
+        }
+    }
+
+}
+
+/// One player's own attributes, in place of drawing them from the distributions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attributes {
+    pub speed: f64,
+    pub reach: f64,
+}
+
+/// Reads players' own attributes from a file; see [`parse_roster`] for the format.
+pub fn load_roster(path: &str) -> Result<BTreeMap<usize, Attributes>, String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("Couldn't read {:?}: {}.", path, error))?;
+    parse_roster(&text)
+}
+
+/// Parses lines of `id,speed,reach`, one player per line.
+/// Blank lines, lines starting with `#` and a leading `id,speed,reach` header are skipped.
+pub fn parse_roster(text: &str) -> Result<BTreeMap<usize, Attributes>, String> {
+    let mut roster = BTreeMap::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || (roster.is_empty() && line.replace(' ', "") == "id,speed,reach") {
+            continue;
+        }
+        let invalid = || format!("Invalid player attributes {:?} on line {}.", line, number + 1);
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 3 {
+            return Err(invalid());
+        }
+        let id = fields[0].parse::<usize>().map_err(|_| invalid())?;
+        let speed = fields[1].parse::<f64>().map_err(|_| invalid())?;
+        let reach = fields[2].parse::<f64>().map_err(|_| invalid())?;
+        if speed < 0. || reach < 0. {
+            return Err(invalid());
+        }
+        roster.insert(id, Attributes { speed, reach });
+    }
+    Ok(roster)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::attributes::{Distribution, Attributes, parse_roster};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn parses_and_samples_distributions() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(Ok(Distribution::Fixed(5.)), "5".parse());
+        assert_eq!(Ok(Distribution::Uniform { low: 3., high: 7. }), "uniform:3,7".parse());
+        assert_eq!(Ok(Distribution::Normal { mean: 5., deviation: 1. }), "normal:5,1".parse());
+        assert!("uniform:7,3".parse::<Distribution>().is_err());
+        assert!("normal:5".parse::<Distribution>().is_err());
+        assert!("poisson:5".parse::<Distribution>().is_err());
+
+        let list: Distribution = "list:1,2,3".parse().unwrap();
+        assert_eq!(vec![1., 2., 3., 1.], (0..4).map(|id| list.sample(id, &mut rng)).collect::<Vec<f64>>());
+        let uniform = Distribution::Uniform { low: 3., high: 7. };
+        assert!((0..100).all(|id| (3.0..7.0).contains(&uniform.sample(id, &mut rng))));
+        let normal = Distribution::Normal { mean: 5., deviation: 1. };
+        let mean = (0..1000).map(|id| normal.sample(id, &mut rng)).sum::<f64>() / 1000.;
+        assert!((mean - 5.).abs() < 0.2, "{:?}", mean);
+        assert_eq!(0., Distribution::Fixed(-1.).sample(0, &mut rng));
+    }
+
+    #[test]
+    fn parses_rosters() {
+        let roster = parse_roster("id,speed,reach\n# the fast one\n0, 8, 15\n\n3,4.5,20\n").unwrap();
+        assert_eq!(vec![(0, Attributes { speed: 8., reach: 15. }), (3, Attributes { speed: 4.5, reach: 20. })],
+                   roster.into_iter().collect::<Vec<_>>());
+        assert!(parse_roster("0,8").is_err());
+        assert!(parse_roster("0,-8,15").is_err());
+    }
+
+}
@@ -0,0 +1,81 @@
+use std::str::FromStr;
+
+/// How players' bodies are kept from overlapping, when they have a body radius.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Collisions {
+    /// A player moving into another stops where their bodies touch.
+    Block,
+    /// Players move freely, then any who overlap are pushed apart evenly.
+    Push,
+}
+
+impl FromStr for Collisions {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "block" => Ok(Collisions::Block),
+            "push" => Ok(Collisions::Push),
+            _ => Err(format!("Unknown collision handling {:?}.", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collisions::Collisions;
+    use crate::agents::agent::{Agent, Player};
+    use crate::action::Action;
+    use crate::environment::Environment;
+    use crate::parameters::{TagParams, DEFAULT_PARAMS};
+    use crate::tag_environment::TagEnvironment;
+    use iced::Point;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Two runners with bodies of radius 1, at x = 10 and `x`.
+    fn pair(collisions: Collisions, x: f32) -> TagEnvironment<Player> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let params = TagParams { width: 100, height: 100, num_players: 0, num_it: 0, collisions, ..DEFAULT_PARAMS };
+        let mut env: TagEnvironment<Player> = TagEnvironment::new(&params);
+        for &(id, x) in &[(0, 10.), (1, x)] {
+            let player = Player::create(id, &params, &mut rng);
+            env.add_agent(Player { position: Point::new(x, 50.), is_it: false, speed: 5., radius: 1., ..player });
+        }
+        env
+    }
+
+    fn gap(env: &TagEnvironment<Player>) -> f32 {
+        env.agents.get(&0).unwrap().position.distance(env.agents.get(&1).unwrap().position)
+    }
+
+    #[test]
+    fn blocked_players_stop_at_each_other() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env = pair(Collisions::Block, 14.);
+        assert_eq!(Ok(()), env.step(0, &Action::Move(Point::new(15., 50.)), &mut rng));
+        assert!(gap(&env) >= 2. - 1e-3 && gap(&env) < 2.1, "{:?}", gap(&env));
+
+        // starting overlapped, they can't get any closer, but can back away
+        let mut env = pair(Collisions::Block, 11.);
+        assert_eq!(Ok(()), env.step(0, &Action::Move(Point::new(12., 50.)), &mut rng));
+        assert_eq!(Point::new(10., 50.), env.agents.get(&0).unwrap().position);
+        assert_eq!(Ok(()), env.step(0, &Action::Move(Point::new(8., 50.)), &mut rng));
+        assert_eq!(Point::new(8., 50.), env.agents.get(&0).unwrap().position);
+    }
+
+    #[test]
+    fn pushed_players_are_moved_apart() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env = pair(Collisions::Push, 14.);
+        assert_eq!(Ok(()), env.step(0, &Action::Move(Point::new(13., 50.)), &mut rng));
+        assert!(gap(&env) >= 2. - 1e-3, "{:?}", gap(&env));
+        assert!(env.agents.get(&1).unwrap().position.x > 14.);
+
+        // players who start overlapped are pushed apart by the next move
+        let mut env = pair(Collisions::Push, 10.5);
+        assert_eq!(Ok(()), env.step(0, &Action::Move(Point::new(10., 51.)), &mut rng));
+        assert!(gap(&env) >= 2. - 1e-3, "{:?}", gap(&env));
+    }
+
+}
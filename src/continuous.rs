@@ -0,0 +1,65 @@
+use iced::Vector;
+use std::str::FromStr;
+
+/// How game time passes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeMode {
+    /// Turn by turn: every player acts once a step, and tags happen between moves.
+    Stepped,
+    /// Players choose velocities, and time runs on to the next moment an 'it' comes within reach of someone
+    /// they can tag (or for a unit of time at most) before they choose again; tags happen at the moment of contact.
+    Continuous,
+    /// Every agent runs as its own task, deciding from snapshots of the game and sending actions back whenever
+    /// it's ready; actions are applied in the order they arrive, so slow deciders fall behind.
+    /// Games played this way can't be replayed exactly.
+    Asynchronous,
+}
+
+impl FromStr for TimeMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "stepped" | "turns" => Ok(TimeMode::Stepped),
+            "continuous" | "events" => Ok(TimeMode::Continuous),
+            "asynchronous" | "async" => Ok(TimeMode::Asynchronous),
+            _ => Err(format!("Unknown time mode {:?}.", value)),
+        }
+    }
+}
+
+/// The earliest time within `horizon` at which two players `offset` apart, moving at `relative` velocity to each other,
+/// come within `range` of each other; 0 if they already are.
+pub fn contact_time(offset: [f32; 2], relative: Vector, range: f32, horizon: f32) -> Option<f32> {
+    let [offset_x, offset_y] = offset;
+    let c = offset_x * offset_x + offset_y * offset_y - range * range;
+    if c <= 0. {
+        return Some(0.);
+    }
+    let a = relative.x * relative.x + relative.y * relative.y;
+    let b = 2. * (offset_x * relative.x + offset_y * relative.y);
+    let discriminant = b * b - 4. * a * c;
+    if a == 0. || discriminant < 0. {
+        return None;
+    }
+    let time = (-b - discriminant.sqrt()) / (2. * a);
+    if (0.0..=horizon).contains(&time) { Some(time) } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::continuous::contact_time;
+    use iced::Vector;
+
+    #[test]
+    fn predicts_contacts() {
+        assert_eq!(Some(0.), contact_time([1., 0.], Vector::new(1., 0.), 2., 1.));
+        assert_eq!(Some(0.5), contact_time([10., 0.], Vector::new(-16., 0.), 2., 1.));
+        assert_eq!(None, contact_time([10., 0.], Vector::new(-4., 0.), 2., 1.));
+        assert_eq!(None, contact_time([10., 0.], Vector::new(16., 0.), 2., 1.));
+        assert_eq!(None, contact_time([10., 0.], Vector::new(0., 0.), 2., 1.));
+        let time = contact_time([10., 3.], Vector::new(-10., 0.), 5., 1.).unwrap();
+        assert!((time - 0.6).abs() < 1e-6, "{:?}", time);
+    }
+
+}
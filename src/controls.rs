@@ -6,12 +6,14 @@ pub(crate) struct Controls {
     toggle_button: button::State,
     next_button: button::State,
     reset_button: button::State,
+    join_button: button::State,
+    leave_button: button::State,
 }
 
 impl Controls {
 
     pub fn view(&mut self,
-            is_playing: bool) -> Element<Message> {
+            is_playing: bool) -> Element<'_, Message> {
         let playback_controls = Row::new()
             .spacing(10)
             .push(
@@ -26,11 +28,23 @@ impl Controls {
                     .on_press(Message::Next)
             );
 
+        let player_controls = Row::new()
+            .spacing(10)
+            .push(
+                Button::new(&mut self.join_button, Text::new("Join"))
+                    .on_press(Message::Join)
+            )
+            .push(
+                Button::new(&mut self.leave_button, Text::new("Leave"))
+                    .on_press(Message::Leave)
+            );
+
         Row::new()
             .padding(10)
             .spacing(20)
             .align_items(Align::Center)
             .push(playback_controls)
+            .push(player_controls)
             .push(
                 Button::new(&mut self.reset_button, Text::new("Reset"))
                     .on_press(Message::Reset)
@@ -1,4 +1,5 @@
 use crate::parameters::TagParams;
+use rand::rngs::StdRng;
 
 /// The main simulation environment trait.
 /// This was originally created with the intention of
@@ -6,12 +7,30 @@ use crate::parameters::TagParams;
 ///     but wound up out of scope.
 pub trait Environment<ACTION, AGENT> {
 
-    fn reset(&mut self, params: TagParams);
+    /// Why an action may be refused.
+    type Rejection;
+
+    /// What happened when a set of actions was applied.
+    type Report;
+
+    fn reset(&mut self, params: &TagParams, rng: &mut StdRng);
 
     fn add_agent(&mut self, agent: AGENT);
 
-    fn step(&mut self, agent: usize, action: &ACTION);
+    /// Takes an agent out of the game, returning it if it was there.
+    fn remove_agent(&mut self, agent: usize) -> Option<AGENT>;
+
+    /// Applies a single action straight away.
+    fn step(&mut self, agent: usize, action: &ACTION, rng: &mut StdRng) -> Result<(), Self::Rejection>;
+
+    /// Applies a set of (agent id, action) pairs as if they all happened at once.
+    fn step_all(&mut self, actions: Vec<(usize, ACTION)>, rng: &mut StdRng) -> Self::Report;
+
+    /// Applies a set of (agent id, action) pairs one after another, in the order given, as one step.
+    fn step_in_order(&mut self, actions: Vec<(usize, ACTION)>, rng: &mut StdRng) -> Self::Report;
 
-    fn step_all(&mut self, actions: Vec<ACTION>);
+    /// Runs the game on in continuous time from a set of (agent id, action) pairs for `horizon`,
+    /// handling events at the moment they happen along the way.
+    fn advance(&mut self, actions: Vec<(usize, ACTION)>, horizon: f32, rng: &mut StdRng) -> Self::Report;
 
 }
\ No newline at end of file
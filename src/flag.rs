@@ -0,0 +1,33 @@
+use iced::Point;
+
+/// A team's flag in capture the flag, which the other team tries to carry back to their own side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Flag {
+    /// The team the flag belongs to.
+    pub team: usize,
+    /// Where the flag stands at the start of the game, and goes back to once it's been captured.
+    pub home: Point,
+    /// Where the flag lies while nobody is carrying it.
+    pub position: Point,
+    /// The player carrying the flag, if anyone is.
+    pub carrier: Option<usize>,
+}
+
+impl Flag {
+
+    pub fn new(team: usize, home: Point) -> Self {
+        Flag { team, home, position: home, carrier: None }
+    }
+
+    /// Puts the flag down at `position`.
+    pub fn drop_at(&mut self, position: Point) {
+        self.position = position;
+        self.carrier = None;
+    }
+
+    /// Puts the flag back on its stand.
+    pub fn return_home(&mut self) {
+        self.drop_at(self.home);
+    }
+
+}
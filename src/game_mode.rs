@@ -0,0 +1,98 @@
+use std::str::FromStr;
+
+/// The game being played, which decides what a tag does and how the game is won.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameMode {
+    /// A tag passes 'it' from the tagger to the target.
+    Classic,
+    /// A tag freezes the target in place, and the tagger stays 'it'. A free runner unfreezes a frozen teammate
+    /// by tagging them; the 'it's win once every runner is frozen.
+    Freeze,
+    /// A tag infects the target, who becomes 'it' while the tagger stays 'it'. The game ends once everyone is infected,
+    /// and is won by the last to hold out.
+    Infection,
+    /// A tag takes the target out of the game, and the tagger stays 'it'; the last runner standing wins.
+    Elimination,
+    /// Two teams, each with their own half of the field, where they can't be tagged. Anyone can tag an opponent
+    /// on their side of the field, and what happens to the tagged player is up to the capture rule.
+    Teams,
+    /// Team tag with a flag on each side: a team scores by carrying the other team's flag back to their own side,
+    /// and wins by scoring enough times.
+    Flag,
+}
+
+impl GameMode {
+
+    /// Whether the game is played between teams, each with their own side of the field.
+    pub fn has_teams(self) -> bool {
+        matches!(self, GameMode::Teams | GameMode::Flag)
+    }
+
+}
+
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "classic" => Ok(GameMode::Classic),
+            "freeze" | "freeze_tag" => Ok(GameMode::Freeze),
+            "infection" | "zombie" => Ok(GameMode::Infection),
+            "elimination" => Ok(GameMode::Elimination),
+            "teams" | "team" => Ok(GameMode::Teams),
+            "flag" | "capture_the_flag" => Ok(GameMode::Flag),
+            _ => Err(format!("Unknown game mode {:?}.", value)),
+        }
+    }
+}
+
+/// What happens to a player tagged in team tag or capture the flag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Capture {
+    /// They're sent to the tagging team's jail, where they stay until a free teammate tags them out.
+    Jail,
+    /// They join the tagging team; the game is won once everyone is on the same team.
+    Switch,
+}
+
+impl FromStr for Capture {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "jail" => Ok(Capture::Jail),
+            "switch" => Ok(Capture::Switch),
+            _ => Err(format!("Unknown capture rule {:?}.", value)),
+        }
+    }
+}
+
+/// The number of teams in team tag and capture the flag.
+pub const TEAMS: usize = 2;
+
+/// The stretch of the field's width, from left to right, that is `team`'s home side.
+pub fn home_side(team: usize, width: f32) -> (f32, f32) {
+    let share = width / TEAMS as f32;
+    (share * team as f32, share * (team + 1) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_mode::{GameMode, Capture, home_side};
+
+    #[test]
+    fn parses_game_modes() {
+        assert_eq!(Ok(GameMode::Classic), "classic".parse());
+        assert_eq!(Ok(GameMode::Freeze), "Freeze".parse());
+        assert_eq!(Ok(GameMode::Infection), "zombie".parse());
+        assert_eq!(Ok(GameMode::Elimination), "elimination".parse());
+        assert_eq!(Ok(GameMode::Teams), "teams".parse());
+        assert_eq!(Ok(GameMode::Flag), "capture_the_flag".parse());
+        assert!(GameMode::Flag.has_teams() && !GameMode::Freeze.has_teams());
+        assert!("hide_and_seek".parse::<GameMode>().is_err());
+        assert_eq!(Ok(Capture::Switch), "switch".parse());
+        assert!("ransom".parse::<Capture>().is_err());
+        assert_eq!((500., 1000.), home_side(1, 1000.));
+    }
+
+}
@@ -2,15 +2,16 @@ use iced::{Application, Command, Element, executor, Column, Container, Length, S
 use crate::simulation::Simulation;
 use crate::time;
 use std::time::{Instant};
-use crate::environment::Environment;
 use crate::parameters::TagParams;
 use crate::agents::agent::Agent;
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    Tick(Instant),
+    Tick(#[allow(dead_code)] Instant),
     TogglePlayback,
     Next,
+    Join,
+    Leave,
     Reset,
 }
 
@@ -35,13 +36,34 @@ impl<X: Agent> Application for Simulation<X> {
 
         match message {
             Message::Tick(_) | Message::Next => {
-                self.step();
+                // a game that's over stays over, however often Next is pressed
+                if self.ended().is_none() {
+                    self.step();
+                    if let Some(reason) = self.ended() {
+                        log::info!("The game is over ({:?}); the winners are {:?}.", reason, self.outcome(reason).winners());
+                    }
+                }
+                if self.ended().is_some() {
+                    self.is_running = false;
+                }
             }
             Message::TogglePlayback => {
                 self.is_running = !self.is_running;
             }
+            Message::Join => {
+                let id = self.join();
+                log::info!("Agent {:?} joined the game.", id);
+            }
+            Message::Leave => {
+                // the newest player leaves first
+                let newest = self.environment.agents.iter().map(|agent| *agent.key()).max();
+                if let Some(id) = newest {
+                    self.leave(id);
+                    log::info!("Agent {:?} left the game.", id);
+                }
+            }
             Message::Reset => {
-                self.environment.reset(self.parameters);
+                self.reset();
             }
         }
 
@@ -52,13 +74,13 @@ impl<X: Agent> Application for Simulation<X> {
         if self.is_running {
             // throttle to update less frequently under load/large numbers of agents
             time::every(std::time::Duration::from_millis(u64::max(10, ((self.parameters.num_players / 50) * 10) as u64)))
-                .map(|instant| Message::Tick(instant))
+                .map(Message::Tick)
         } else {
             Subscription::none()
         }
     }
 
-    fn view(&mut self) -> Element<Message> {
+    fn view(&mut self) -> Element<'_, Message> {
         let controls = self.controls.view(self.is_running);
 
         let content = Column::new()
@@ -70,8 +92,8 @@ impl<X: Agent> Application for Simulation<X> {
             .push(controls);
 
         Container::new(content)
-            .width(Length::Units(self.environment.width as u16))
-            .height(Length::Units(self.environment.height as u16 + 50))
+            .width(Length::Units(self.environment.arena.width as u16))
+            .height(Length::Units(self.environment.arena.height as u16 + 50))
             .center_x()
             .center_y()
             .into()
@@ -0,0 +1,61 @@
+/// One player catching the infection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Infection {
+    pub infected: usize,
+    /// Who infected them; `None` for the players who started the game infected.
+    pub by: Option<usize>,
+    /// The number of steps taken by the time they were infected; 0 for the players who started the game infected.
+    pub step: u64,
+}
+
+/// How the infection spread through an infection tag game.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Epidemic {
+    /// The number of players infected at each point in game time, starting from the beginning of the game.
+    pub curve: Vec<(f64, usize)>,
+    /// Every infection, in the order they happened; together they make up the tree of who infected whom.
+    pub infections: Vec<Infection>,
+}
+
+impl Epidemic {
+
+    /// The players infected by `player`, in the order they were infected.
+    pub fn infected_by(&self, player: usize) -> Vec<usize> {
+        self.infections.iter().filter(|infection| infection.by == Some(player)).map(|infection| infection.infected).collect()
+    }
+
+    /// How many infections separate `player` from one of the players who started the game infected,
+    /// or `None` if they were never infected.
+    pub fn generation(&self, player: usize) -> Option<usize> {
+        let mut generation = 0;
+        let mut infection = self.infections.iter().find(|infection| infection.infected == player)?;
+        while let Some(by) = infection.by {
+            generation += 1;
+            infection = self.infections.iter().find(|infection| infection.infected == by)?;
+        }
+        Some(generation)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::infection::{Epidemic, Infection};
+
+    #[test]
+    fn traces_the_infection_tree() {
+        let epidemic = Epidemic {
+            curve: vec![(0., 1), (1., 2), (2., 4)],
+            infections: vec![
+                Infection { infected: 3, by: None, step: 0 },
+                Infection { infected: 0, by: Some(3), step: 1 },
+                Infection { infected: 2, by: Some(0), step: 2 },
+                Infection { infected: 1, by: Some(3), step: 2 },
+            ],
+        };
+        assert_eq!(vec![0, 1], epidemic.infected_by(3));
+        assert_eq!(vec![Some(0), Some(1), Some(2)], vec![epidemic.generation(3), epidemic.generation(0), epidemic.generation(2)]);
+        assert_eq!(None, epidemic.generation(4));
+    }
+
+}
@@ -0,0 +1,73 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+/// What an agent that runs out of time to decide does instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fallback {
+    StandStill,
+    /// Make the same move as last time, or stand still if there isn't one.
+    Repeat,
+}
+
+impl FromStr for Fallback {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "stand" | "stand_still" => Ok(Fallback::StandStill),
+            "repeat" => Ok(Fallback::Repeat),
+            _ => Err(format!("Unknown fallback {:?}.", value)),
+        }
+    }
+}
+
+/// How long an agent has taken over its decisions.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Latency {
+    pub decisions: u64,
+    /// The decisions that weren't made in time.
+    pub overruns: u64,
+    pub total: Duration,
+    pub longest: Duration,
+}
+
+impl Latency {
+
+    pub fn record(&mut self, latency: Duration, overran: bool) {
+        self.decisions += 1;
+        if overran {
+            self.overruns += 1;
+        }
+        self.total += latency;
+        self.longest = self.longest.max(latency);
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.decisions == 0 {
+            Duration::default()
+        } else {
+            self.total / self.decisions as u32
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::latency::{Fallback, Latency};
+    use std::time::Duration;
+
+    #[test]
+    fn records_latencies() {
+        assert_eq!(Ok(Fallback::Repeat), "repeat".parse());
+        assert!("sulk".parse::<Fallback>().is_err());
+
+        let mut latency = Latency::default();
+        assert_eq!(Duration::default(), latency.mean());
+        latency.record(Duration::from_millis(2), false);
+        latency.record(Duration::from_millis(6), true);
+        assert_eq!(Latency { decisions: 2, overruns: 1, total: Duration::from_millis(8), longest: Duration::from_millis(6) }, latency);
+        assert_eq!(Duration::from_millis(4), latency.mean());
+    }
+
+}
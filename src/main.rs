@@ -5,10 +5,16 @@ extern crate test;
 use crate::simulation::Simulation;
 use clap::{App, Arg, ArgMatches};
 use crate::parameters::{TagParams, DEFAULT_PARAMS};
+use crate::rules::TagRules;
+use crate::motion::Motion;
+use crate::attributes::load_roster;
 use std::fmt::Debug;
 use crate::agents::agent_type::AgentType;
 use crate::agents::agent::Player;
 use crate::agents::basic_directional::DirectionalAgent;
+use crate::agents::mixed::{MixedAgent, Population};
+use crate::agents::custom::CustomAgent;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 mod environment;
 mod tag_environment;
@@ -19,6 +25,22 @@ mod iced_ui;
 mod controls;
 mod time;
 mod agents;
+mod spatial_index;
+mod resolution;
+mod rules;
+mod arena;
+mod obstacles;
+mod collisions;
+mod motion;
+mod attributes;
+mod continuous;
+mod runtime;
+mod latency;
+mod schedule;
+mod outcome;
+mod game_mode;
+mod infection;
+mod flag;
 
 fn main() {
     env_logger::init();
@@ -30,12 +52,24 @@ fn main() {
             .short("s")
             .long("speed")
             .takes_value(true)
-            .help("The maximum distance per turn of a player/agent"))
+            .help("The maximum distance per turn of a player/agent: a number, uniform:low,high, normal:mean,deviation or list:a,b,c,..."))
         .arg(Arg::with_name("proximity")
             .short("p")
             .long("proximity")
             .takes_value(true)
-            .help("The distance within which one player/agent may tag another"))
+            .help("The distance within which one player/agent may tag another, as a number or distribution like --speed"))
+        .arg(Arg::with_name("it_speed")
+            .long("it_speed")
+            .takes_value(true)
+            .help("The speed of the players who start as 'it', as a number or distribution like --speed (the same as everyone else's by default)."))
+        .arg(Arg::with_name("it_proximity")
+            .long("it_proximity")
+            .takes_value(true)
+            .help("The reach of the players who start as 'it', as a number or distribution like --speed (the same as everyone else's by default)."))
+        .arg(Arg::with_name("attributes")
+            .long("attributes")
+            .takes_value(true)
+            .help("A file of id,speed,reach lines giving players their own attributes in place of the distributions."))
         .arg(Arg::with_name("width")
             .short("w")
             .long("width")
@@ -61,26 +95,192 @@ fn main() {
             .long("directional_agent")
             .takes_value(false)
             .help("Have players/agents use a simple directional strategy."))
+        .arg(Arg::with_name("mix")
+            .long("mix")
+            .takes_value(true)
+            .help("A mix of strategies on the field at once, e.g. default:30,directional:20; sets the number of players."))
         .arg(Arg::with_name("text_numbers")
             .short("t")
             .long("text_numbers")
             .takes_value(false)
             .help("Whether the players should be numbered in the UI."))
+        .arg(Arg::with_name("seed")
+            .short("r")
+            .long("seed")
+            .takes_value(true)
+            .help("The seed for the random number generator, to reproduce a previous game."))
+        .arg(Arg::with_name("threads")
+            .short("j")
+            .long("threads")
+            .takes_value(true)
+            .help("The number of threads agents choose their actions on (0 for one per core)."))
+        .arg(Arg::with_name("conflict_policy")
+            .short("c")
+            .long("conflict_policy")
+            .takes_value(true)
+            .help("Who wins when several 'it's tag the same player at once: closest, random or earliest."))
+        .arg(Arg::with_name("tag_back_cooldown")
+            .long("tag_back_cooldown")
+            .takes_value(true)
+            .help("The number of steps before a player may tag back whoever tagged them (never, by default)."))
+        .arg(Arg::with_name("immunity")
+            .long("immunity")
+            .takes_value(true)
+            .help("The number of steps after passing 'it' on during which a player can't be tagged."))
+        .arg(Arg::with_name("miss_near")
+            .long("miss_near")
+            .takes_value(true)
+            .help("The chance of a tag missing a target right next to the tagger."))
+        .arg(Arg::with_name("miss_far")
+            .long("miss_far")
+            .takes_value(true)
+            .help("The chance of a tag missing a target at the edge of the tagger's range."))
+        .arg(Arg::with_name("hit_test")
+            .long("hit_test")
+            .takes_value(true)
+            .help("How range is measured for tags: center (distance between centres) or overlap (of the players' circles)."))
+        .arg(Arg::with_name("boundary")
+            .short("b")
+            .long("boundary")
+            .takes_value(true)
+            .help("What happens at the edges of the field: clamp, wrap, reflect or eliminate."))
+        .arg(Arg::with_name("obstacle")
+            .short("o")
+            .long("obstacle")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .help("An obstacle on the field, as circle:x,y,radius or polygon:x1,y1,x2,y2,x3,y3,... (may be repeated)."))
+        .arg(Arg::with_name("body_radius")
+            .long("body_radius")
+            .takes_value(true)
+            .help("The radius of each player's body, which others can't overlap (0 lets players pass through each other)."))
+        .arg(Arg::with_name("collisions")
+            .long("collisions")
+            .takes_value(true)
+            .help("How players' bodies collide: block (moves stop on contact) or push (overlapping players are pushed apart)."))
+        .arg(Arg::with_name("motion")
+            .short("m")
+            .long("motion")
+            .takes_value(true)
+            .help("How players move: holonomic (any direction, turning instantly), momentum (limited acceleration) or dubins (limited turning)."))
+        .arg(Arg::with_name("acceleration")
+            .long("acceleration")
+            .takes_value(true)
+            .help("The most a player's velocity can change in a step under the momentum model."))
+        .arg(Arg::with_name("turning_radius")
+            .long("turning_radius")
+            .takes_value(true)
+            .help("The tightest circle a player can turn in under the dubins model."))
+        .arg(Arg::with_name("sprint_speed")
+            .long("sprint_speed")
+            .takes_value(true)
+            .help("The most distance a player can cover in a sprinting step."))
+        .arg(Arg::with_name("stamina")
+            .long("stamina")
+            .takes_value(true)
+            .help("How much stamina players start with; sprinting uses up the distance gone beyond their speed."))
+        .arg(Arg::with_name("recovery")
+            .long("recovery")
+            .takes_value(true)
+            .help("How much stamina players recover each step they move without sprinting."))
+        .arg(Arg::with_name("time")
+            .long("time")
+            .takes_value(true)
+            .help("How game time passes: stepped (turn by turn), continuous (from one contact to the next) or async (agents act whenever they're ready)."))
+        .arg(Arg::with_name("schedule")
+            .long("schedule")
+            .takes_value(true)
+            .help("The order players act in within a stepped game's step: simultaneous, random_order, random_activation or alternating."))
+        .arg(Arg::with_name("budget")
+            .long("budget")
+            .takes_value(true)
+            .help("How many milliseconds each agent has to decide on an action (0 for no limit)."))
+        .arg(Arg::with_name("fallback")
+            .long("fallback")
+            .takes_value(true)
+            .help("What an agent that runs out of time does instead: stand (still) or repeat (its last move)."))
+        .arg(Arg::with_name("mode")
+            .long("mode")
+            .takes_value(true)
+            .help("The game to play: classic, freeze, infection, elimination, teams or flag (capture the flag)."))
+        .arg(Arg::with_name("capture")
+            .long("capture")
+            .takes_value(true)
+            .help("What happens to a player tagged in team tag or capture the flag: jail (until a teammate frees them) or switch (to the tagging team)."))
+        .arg(Arg::with_name("flags_to_win")
+            .long("flags_to_win")
+            .takes_value(true)
+            .help("The number of flags a team has to capture to win capture the flag."))
+        .arg(Arg::with_name("end")
+            .long("end")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .help("What ends the game: steps:count, time:seconds, everyone_it or won (may be repeated; the first met ends it)."))
         .get_matches();
 
     log::info!("Starting up Tag Simulator.");
 
-    let agent_type = if matches.is_present("directional_agent") { AgentType::BasicDirectional } else { AgentType::Default };
+    let population: Option<Population> = extract_optional("mix", &matches);
+    let agent_type = if population.is_some() {
+        AgentType::Mixed
+    } else if matches.is_present("directional_agent") {
+        AgentType::BasicDirectional
+    } else {
+        AgentType::Default
+    };
 
     let mut parameters: TagParams = TagParams {
         speed: extract("speed", &matches, DEFAULT_PARAMS.speed),
         proximity: extract("proximity", &matches, DEFAULT_PARAMS.proximity),
+        it_speed: extract_optional("it_speed", &matches),
+        it_proximity: extract_optional("it_proximity", &matches),
+        roster: match matches.value_of("attributes").map(load_roster) {
+            None => DEFAULT_PARAMS.roster,
+            Some(Ok(roster)) => roster,
+            Some(Err(error)) => {
+                log::warn!("{} Drawing every player's attributes from the distributions.", error);
+                DEFAULT_PARAMS.roster
+            }
+        },
         width: extract("width", &matches, DEFAULT_PARAMS.width),
         height: extract("height", &matches, DEFAULT_PARAMS.height),
-        num_players: extract("num_players", &matches, DEFAULT_PARAMS.num_players),
+        num_players: population.as_ref().map_or_else(|| extract("num_players", &matches, DEFAULT_PARAMS.num_players), Population::total),
         agent_type,
+        population: population.unwrap_or(DEFAULT_PARAMS.population),
         numbered: matches.is_present("text_numbers"),
         num_it: extract("num_it", &matches, DEFAULT_PARAMS.num_it),
+        seed: extract("seed", &matches, random_seed()),
+        threads: extract("threads", &matches, DEFAULT_PARAMS.threads),
+        conflict_policy: extract("conflict_policy", &matches, DEFAULT_PARAMS.conflict_policy),
+        rules: TagRules {
+            tag_back_cooldown: extract("tag_back_cooldown", &matches, DEFAULT_PARAMS.rules.tag_back_cooldown),
+            immunity: extract("immunity", &matches, DEFAULT_PARAMS.rules.immunity),
+            miss_near: extract("miss_near", &matches, DEFAULT_PARAMS.rules.miss_near),
+            miss_far: extract("miss_far", &matches, DEFAULT_PARAMS.rules.miss_far),
+            hit_test: extract("hit_test", &matches, DEFAULT_PARAMS.rules.hit_test),
+        },
+        boundary: extract("boundary", &matches, DEFAULT_PARAMS.boundary),
+        obstacles: extract_all("obstacle", &matches),
+        body_radius: extract("body_radius", &matches, DEFAULT_PARAMS.body_radius),
+        collisions: extract("collisions", &matches, DEFAULT_PARAMS.collisions),
+        motion: Motion {
+            model: extract("motion", &matches, DEFAULT_PARAMS.motion.model),
+            acceleration: extract("acceleration", &matches, DEFAULT_PARAMS.motion.acceleration),
+            turning_radius: extract("turning_radius", &matches, DEFAULT_PARAMS.motion.turning_radius),
+        },
+        sprint_speed: extract("sprint_speed", &matches, DEFAULT_PARAMS.sprint_speed),
+        stamina: extract("stamina", &matches, DEFAULT_PARAMS.stamina),
+        recovery: extract("recovery", &matches, DEFAULT_PARAMS.recovery),
+        time_mode: extract("time", &matches, DEFAULT_PARAMS.time_mode),
+        schedule: extract("schedule", &matches, DEFAULT_PARAMS.schedule),
+        budget: extract("budget", &matches, DEFAULT_PARAMS.budget),
+        fallback: extract("fallback", &matches, DEFAULT_PARAMS.fallback),
+        end: extract_all("end", &matches),
+        mode: extract("mode", &matches, DEFAULT_PARAMS.mode),
+        capture: extract("capture", &matches, DEFAULT_PARAMS.capture),
+        flags_to_win: extract("flags_to_win", &matches, DEFAULT_PARAMS.flags_to_win),
     };
 
     parameters.num_it = usize::min(parameters.num_it, parameters.num_players - 1);
@@ -92,10 +292,24 @@ fn main() {
         AgentType::BasicDirectional => {
             Simulation::<DirectionalAgent>::run_gui(parameters);
         }
+        AgentType::Mixed => {
+            Simulation::<MixedAgent<CustomAgent>>::run_gui(parameters);
+        }
+        AgentType::Custom => {
+            Simulation::<CustomAgent>::run_gui(parameters);
+        }
     }
 
 }
 
+/// A seed for when none is given, taken from the clock; it is logged by `extract` so the game can be re-run.
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or(DEFAULT_PARAMS.seed)
+}
+
 fn extract<TYPE: Debug + std::str::FromStr>(name: &str, args: &ArgMatches, default: TYPE) -> TYPE {
     match args.value_of(name) {
         None => {
@@ -115,3 +329,28 @@ fn extract<TYPE: Debug + std::str::FromStr>(name: &str, args: &ArgMatches, defau
         }
     }
 }
+
+/// The value given for an option with no default, if it's valid.
+fn extract_optional<TYPE: Debug + std::str::FromStr>(name: &str, args: &ArgMatches) -> Option<TYPE> {
+    let value = args.value_of(name)?;
+    match value.parse::<TYPE>() {
+        Ok(parsed) => Some(parsed),
+        Err(_) => {
+            log::warn!("Value {:?} passed for {:?} is invalid and was ignored.", value, name);
+            None
+        }
+    }
+}
+
+/// Every valid value given for an option that may be repeated.
+fn extract_all<TYPE: Debug + std::str::FromStr>(name: &str, args: &ArgMatches) -> Vec<TYPE> {
+    args.values_of(name).into_iter().flatten()
+        .filter_map(|value| match value.parse::<TYPE>() {
+            Ok(parsed) => Some(parsed),
+            Err(_) => {
+                log::warn!("Value {:?} passed for {:?} is invalid and was ignored.", value, name);
+                None
+            }
+        })
+        .collect()
+}
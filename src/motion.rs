@@ -0,0 +1,118 @@
+use iced::Vector;
+use std::str::FromStr;
+use std::f32::consts::PI;
+
+/// How players' controls turn into movement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MotionModel {
+    /// Players move at whatever velocity they ask for, up to their speed, turning instantly.
+    Holonomic,
+    /// A double integrator: players have momentum, and can only change their velocity by up to `acceleration` a step.
+    Momentum,
+    /// A Dubins car: players move the way they're facing, and can't turn tighter than `turning_radius`.
+    Dubins,
+}
+
+impl FromStr for MotionModel {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "holonomic" => Ok(MotionModel::Holonomic),
+            "momentum" | "double_integrator" => Ok(MotionModel::Momentum),
+            "dubins" | "car" => Ok(MotionModel::Dubins),
+            _ => Err(format!("Unknown motion model {:?}.", value)),
+        }
+    }
+}
+
+/// The motion model and its limits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Motion {
+
+    pub model: MotionModel,
+
+    /// The most a player's velocity can change in one step under the momentum model.
+    pub acceleration: f32,
+
+    /// The tightest circle a player can turn in under the Dubins model.
+    pub turning_radius: f32,
+
+}
+
+impl Motion {
+
+    /// The velocity and heading a player moving at `velocity` and facing `heading` ends up with for the step
+    /// when asking to move at `desired`, limited to `speed`.
+    /// Players move by their new velocity over the step.
+    pub fn integrate(&self, velocity: Vector, heading: f32, desired: Vector, speed: f32) -> (Vector, f32) {
+        let desired = limit(desired, speed);
+        let velocity = match self.model {
+            MotionModel::Holonomic => desired,
+            MotionModel::Momentum => limit(velocity + limit(desired - velocity, self.acceleration), speed),
+            MotionModel::Dubins => {
+                let speed = length(desired);
+                if speed == 0. {
+                    return (Vector::new(0., 0.), heading);
+                }
+                let most = if self.turning_radius > 0. { speed / self.turning_radius } else { PI };
+                let turn = angle_between(heading, desired.y.atan2(desired.x)).max(-most).min(most);
+                let heading = heading + turn;
+                return (Vector::new(speed * heading.cos(), speed * heading.sin()), heading);
+            }
+        };
+        let heading = if length(velocity) > 0. { velocity.y.atan2(velocity.x) } else { heading };
+        (velocity, heading)
+    }
+
+}
+
+pub fn length(vector: Vector) -> f32 {
+    (vector.x * vector.x + vector.y * vector.y).sqrt()
+}
+
+/// The vector scaled down to `most` long, if it's longer.
+fn limit(vector: Vector, most: f32) -> Vector {
+    let length = length(vector);
+    if length <= most {
+        vector
+    } else {
+        Vector::new(vector.x * most / length, vector.y * most / length)
+    }
+}
+
+/// The smallest turn from one heading to another, between -π and π.
+fn angle_between(from: f32, to: f32) -> f32 {
+    (to - from + PI).rem_euclid(2. * PI) - PI
+}
+
+/// Players move at whatever velocity they ask for; the acceleration and turning radius come into play with the other models.
+pub(crate) const DEFAULT_MOTION: Motion = Motion {
+    model: MotionModel::Holonomic,
+    acceleration: 1.0,
+    turning_radius: 20.0,
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::motion::{DEFAULT_MOTION, Motion, MotionModel};
+    use iced::Vector;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn integrates_each_model() {
+        let still = Vector::new(0., 0.);
+        assert_eq!((Vector::new(3., 4.), 4f32.atan2(3.)), DEFAULT_MOTION.integrate(still, 0., Vector::new(6., 8.), 5.));
+
+        let momentum = Motion { model: MotionModel::Momentum, ..DEFAULT_MOTION };
+        assert_eq!(Vector::new(1., 0.), momentum.integrate(still, 0., Vector::new(5., 0.), 5.).0);
+        assert_eq!(Vector::new(4., 0.), momentum.integrate(Vector::new(5., 0.), 0., Vector::new(-5., 0.), 5.).0);
+
+        let car = Motion { model: MotionModel::Dubins, turning_radius: 10., ..DEFAULT_MOTION };
+        let (velocity, heading) = car.integrate(still, 0., Vector::new(0., 5.), 5.);
+        assert!((heading - 0.5).abs() < 1e-6);
+        assert!((velocity.x - 5. * 0.5f32.cos()).abs() < 1e-6 && (velocity.y - 5. * 0.5f32.sin()).abs() < 1e-6);
+        assert_eq!((Vector::new(0., 0.), FRAC_PI_2), car.integrate(Vector::new(0., 5.), FRAC_PI_2, still, 5.));
+    }
+
+}
@@ -0,0 +1,141 @@
+use iced::Point;
+use iced::canvas::Path;
+use std::str::FromStr;
+
+/// A fixed obstacle on the field, which players can neither move nor tag through.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Obstacle {
+    Circle { centre: Point, radius: f32 },
+    /// A polygon given by its corners in order; the last corner joins back up with the first.
+    Polygon(Vec<Point>),
+}
+
+impl FromStr for Obstacle {
+    type Err = String;
+
+    /// Parses `circle:x,y,radius` or `polygon:x1,y1,x2,y2,x3,y3,...`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid obstacle {:?}.", value);
+        let mut parts = value.splitn(2, ':');
+        let kind = parts.next().unwrap_or_default().to_lowercase();
+        let numbers: Vec<f32> = parts.next().ok_or_else(invalid)?
+            .split(',')
+            .map(|number| number.trim().parse::<f32>())
+            .collect::<Result<Vec<f32>, _>>()
+            .map_err(|_| invalid())?;
+        match kind.as_str() {
+            "circle" if numbers.len() == 3 && numbers[2] > 0. => Ok(Obstacle::Circle {
+                centre: Point::new(numbers[0], numbers[1]),
+                radius: numbers[2],
+            }),
+            "polygon" if numbers.len() >= 6 && numbers.chunks_exact(2).remainder().is_empty() => Ok(Obstacle::Polygon(
+                numbers.chunks(2).map(|corner| Point::new(corner[0], corner[1])).collect()
+            )),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Obstacle {
+
+    pub fn contains(&self, point: Point) -> bool {
+        match self {
+            Obstacle::Circle { centre, radius } => centre.distance(point) < *radius,
+            Obstacle::Polygon(corners) => {
+                // count the edges crossed by a ray running right from the point
+                let mut inside = false;
+                for (a, b) in Obstacle::edges(corners) {
+                    if (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
+        }
+    }
+
+    /// How far along the segment from `from` to `to` (as a fraction of its length) it first runs into the obstacle.
+    /// A segment that starts inside the obstacle isn't stopped by it, so that anyone caught inside can get out.
+    pub fn hit(&self, from: Point, to: Point) -> Option<f32> {
+        if self.contains(from) {
+            return None;
+        }
+        let (delta_x, delta_y) = (to.x - from.x, to.y - from.y);
+        match self {
+            Obstacle::Circle { centre, radius } => {
+                let (offset_x, offset_y) = (from.x - centre.x, from.y - centre.y);
+                let a = delta_x * delta_x + delta_y * delta_y;
+                let b = 2. * (offset_x * delta_x + offset_y * delta_y);
+                let c = offset_x * offset_x + offset_y * offset_y - radius * radius;
+                let discriminant = b * b - 4. * a * c;
+                if a == 0. || discriminant < 0. {
+                    return None;
+                }
+                let t = (-b - discriminant.sqrt()) / (2. * a);
+                if (0.0..=1.0).contains(&t) { Some(t) } else { None }
+            }
+            Obstacle::Polygon(corners) => {
+                Obstacle::edges(corners)
+                    .filter_map(|(a, b)| {
+                        let (edge_x, edge_y) = (b.x - a.x, b.y - a.y);
+                        let denominator = delta_x * edge_y - delta_y * edge_x;
+                        if denominator == 0. {
+                            return None;
+                        }
+                        let t = ((a.x - from.x) * edge_y - (a.y - from.y) * edge_x) / denominator;
+                        let u = ((a.x - from.x) * delta_y - (a.y - from.y) * delta_x) / denominator;
+                        if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) { Some(t) } else { None }
+                    })
+                    .fold(None, |first: Option<f32>, t| Some(first.map_or(t, |first| first.min(t))))
+            }
+        }
+    }
+
+    pub fn path(&self) -> Path {
+        match self {
+            Obstacle::Circle { centre, radius } => Path::circle(*centre, *radius),
+            Obstacle::Polygon(corners) => Path::new(|builder| {
+                builder.move_to(corners[0]);
+                for corner in &corners[1..] {
+                    builder.line_to(*corner);
+                }
+                builder.close();
+            }),
+        }
+    }
+
+    fn edges(corners: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+        corners.iter().zip(corners.iter().cycle().skip(1)).map(|(a, b)| (*a, *b))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::obstacles::Obstacle;
+    use iced::Point;
+
+    #[test]
+    fn parses_obstacles() {
+        assert_eq!(Ok(Obstacle::Circle { centre: Point::new(5., 5.), radius: 2. }), "circle:5,5,2".parse());
+        assert_eq!(Ok(Obstacle::Polygon(vec![Point::new(0., 0.), Point::new(1., 0.), Point::new(1., 1.)])), "polygon:0,0,1,0,1,1".parse());
+        assert!("circle:5,5".parse::<Obstacle>().is_err());
+        assert!("polygon:0,0,1,0".parse::<Obstacle>().is_err());
+        assert!("square:0,0,1".parse::<Obstacle>().is_err());
+    }
+
+    #[test]
+    fn finds_collisions() {
+        let circle = Obstacle::Circle { centre: Point::new(5., 5.), radius: 2. };
+        assert_eq!(Some(0.3), circle.hit(Point::new(0., 5.), Point::new(10., 5.)));
+        assert_eq!(None, circle.hit(Point::new(0., 0.), Point::new(10., 0.)));
+        assert_eq!(None, circle.hit(Point::new(5., 5.), Point::new(10., 5.)));
+
+        let square = Obstacle::Polygon(vec![Point::new(4., 4.), Point::new(6., 4.), Point::new(6., 6.), Point::new(4., 6.)]);
+        assert!(square.contains(Point::new(5., 5.)));
+        assert!(!square.contains(Point::new(7., 5.)));
+        assert_eq!(Some(0.4), square.hit(Point::new(0., 5.), Point::new(10., 5.)));
+        assert_eq!(None, square.hit(Point::new(0., 5.), Point::new(3., 5.)));
+    }
+
+}
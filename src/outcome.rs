@@ -0,0 +1,159 @@
+use crate::infection::Epidemic;
+use std::str::FromStr;
+
+/// Something that ends a game once it's met; a game with several ends at the first of them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EndCondition {
+    /// The game has run for this many steps.
+    Steps(u64),
+    /// The game has been running for this many seconds of real time.
+    Time(f64),
+    /// Every player in the game has been 'it' at least once.
+    EveryoneIt,
+    /// The game has been won, in whatever way the game mode is won.
+    Won,
+}
+
+impl FromStr for EndCondition {
+    type Err = String;
+
+    /// Parses `steps:count`, `time:seconds`, `everyone_it` or `won`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid end condition {:?}.", value);
+        let mut parts = value.splitn(2, ':');
+        let kind = parts.next().unwrap_or_default().trim().to_lowercase();
+        match (kind.as_str(), parts.next().map(str::trim)) {
+            ("steps", Some(steps)) => steps.parse::<u64>().map(EndCondition::Steps).map_err(|_| invalid()),
+            ("time", Some(seconds)) => match seconds.parse::<f64>() {
+                Ok(seconds) if seconds >= 0. => Ok(EndCondition::Time(seconds)),
+                _ => Err(invalid()),
+            },
+            ("everyone_it", None) => Ok(EndCondition::EveryoneIt),
+            ("won" | "win", None) => Ok(EndCondition::Won),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Why a game ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EndReason {
+    /// The step limit, from the end conditions or the number of steps asked for, was reached.
+    StepLimit,
+    TimeLimit,
+    EveryoneIt,
+    Won,
+    /// Everyone in an infection tag game was infected; this ends the game whatever the end conditions.
+    AllInfected,
+    /// The game was stopped before any end condition was met.
+    Stopped,
+}
+
+/// How one player did over a game.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PlayerResult {
+    pub id: usize,
+    /// Their team in team tag, the last they were seen on.
+    pub team: usize,
+    /// The tags they made.
+    pub tags: usize,
+    pub times_tagged: usize,
+    /// The frozen teammates they set free.
+    pub rescues: usize,
+    /// The flags they carried home in capture the flag.
+    pub captures: usize,
+    /// The game time they spent as 'it'.
+    pub time_it: f64,
+    /// How many of their actions were rejected as against the rules.
+    pub violations: usize,
+    pub been_it: bool,
+    /// Whether they were 'it' when the game ended.
+    pub is_it: bool,
+    /// Whether they were still in the game when it ended.
+    pub in_game: bool,
+    pub won: bool,
+}
+
+/// A player taken out of the game, by a tag in elimination tag or by going out of bounds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Elimination {
+    pub player: usize,
+    /// Who tagged them out; `None` if they went out of bounds.
+    pub by: Option<usize>,
+    /// The number of steps taken by the time they were eliminated.
+    pub step: u64,
+    /// The game time by which they were eliminated.
+    pub time: f64,
+}
+
+/// How a game ended, and how everyone who played in it did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameOutcome {
+    pub reason: EndReason,
+    /// The number of steps the game ran for.
+    pub steps: u64,
+    /// The game time the game ran for.
+    pub time: f64,
+    /// Every player who took part, including any who left, in order of id.
+    pub players: Vec<PlayerResult>,
+    /// How the infection spread, in infection tag.
+    pub epidemic: Option<Epidemic>,
+    /// The players eliminated, in the order they went out.
+    pub eliminations: Vec<Elimination>,
+    /// The number of flags each team captured, by team, in capture the flag.
+    pub scores: Option<Vec<usize>>,
+}
+
+impl GameOutcome {
+
+    /// The ids of the players who won.
+    pub fn winners(&self) -> Vec<usize> {
+        self.players.iter().filter(|player| player.won).map(|player| player.id).collect()
+    }
+
+    /// The players ranked by how long they survived: those still in the game when it ended, winners first,
+    /// then the eliminated, latest out first. Players who left the game of their own accord aren't ranked.
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut survivors: Vec<&PlayerResult> = self.players.iter().filter(|player| player.in_game).collect();
+        survivors.sort_by_key(|player| !player.won);
+        survivors.into_iter().map(|player| player.id)
+            .chain(self.eliminations.iter().rev().map(|elimination| elimination.player))
+            .collect()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::outcome::{EndCondition, Elimination, GameOutcome, EndReason, PlayerResult};
+
+    #[test]
+    fn parses_end_conditions() {
+        assert_eq!(Ok(EndCondition::Steps(500)), "steps:500".parse());
+        assert_eq!(Ok(EndCondition::Time(2.5)), "time:2.5".parse());
+        assert_eq!(Ok(EndCondition::EveryoneIt), "everyone_it".parse());
+        assert_eq!(Ok(EndCondition::Won), "Won".parse());
+        assert!("steps".parse::<EndCondition>().is_err());
+        assert!("time:-1".parse::<EndCondition>().is_err());
+        assert!("won:3".parse::<EndCondition>().is_err());
+    }
+
+    #[test]
+    fn ranks_by_survival() {
+        let player = |id: usize, in_game: bool, won: bool| PlayerResult { id, in_game, won, ..PlayerResult::default() };
+        let outcome = GameOutcome {
+            reason: EndReason::StepLimit,
+            steps: 10,
+            time: 10.,
+            players: vec![player(0, true, false), player(1, false, false), player(2, false, false), player(3, true, true), player(4, false, false)],
+            epidemic: None,
+            scores: None,
+            eliminations: vec![
+                Elimination { player: 2, by: Some(0), step: 3, time: 3. },
+                Elimination { player: 1, by: None, step: 7, time: 7. },
+            ],
+        };
+        assert_eq!(vec![3, 0, 1, 2], outcome.ranking());
+    }
+
+}
@@ -1,13 +1,36 @@
 use crate::agents::agent_type::AgentType;
+use crate::agents::mixed::Population;
+use crate::resolution::ConflictPolicy;
+use crate::rules::{TagRules, DEFAULT_RULES};
+use crate::arena::Boundary;
+use crate::obstacles::Obstacle;
+use crate::collisions::Collisions;
+use crate::motion::{Motion, DEFAULT_MOTION};
+use crate::continuous::TimeMode;
+use crate::schedule::Schedule;
+use crate::outcome::EndCondition;
+use crate::game_mode::{GameMode, Capture};
+use crate::latency::Fallback;
+use crate::attributes::{Distribution, Attributes};
+use std::collections::BTreeMap;
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct TagParams {
 
-    /// The maximum distance per turn of a player/agent.
-    pub speed: f64,
+    /// The maximum distance per turn of a player/agent, drawn for each player from the distribution.
+    pub speed: Distribution,
 
-    /// The distance within which one player/agent may tag another.
-    pub proximity: f64,
+    /// The distance within which one player/agent may tag another, drawn for each player from the distribution.
+    pub proximity: Distribution,
+
+    /// Where the speed of the players who start as 'it' is drawn from instead, if anywhere.
+    pub it_speed: Option<Distribution>,
+
+    /// Where the reach of the players who start as 'it' is drawn from instead, if anywhere.
+    pub it_proximity: Option<Distribution>,
+
+    /// Players' own speed and reach by id, which take the place of the distributions.
+    pub roster: BTreeMap<usize, Attributes>,
 
     /// The width of the field.
     pub width: u64,
@@ -21,12 +44,76 @@ pub struct TagParams {
     /// What type of agent the players should use.
     pub agent_type: AgentType,
 
+    /// How many players use each strategy, for a mixed population.
+    pub population: Population,
+
     /// Whether the rendered players should be numbered (has a performance cost).
     pub numbered: bool,
 
     /// The number of players that are 'it'.
     pub num_it: usize,
 
+    /// The seed for the simulation's random number generator; the same seed and parameters reproduce a game exactly.
+    pub seed: u64,
+
+    /// The number of threads agents choose their actions on; 0 uses one per logical core.
+    pub threads: usize,
+
+    /// How competing tags on the same player in one step are settled.
+    pub conflict_policy: ConflictPolicy,
+
+    /// The rules of tagging.
+    pub rules: TagRules,
+
+    /// What happens to players who move past the edge of the field.
+    pub boundary: Boundary,
+
+    /// Obstacles on the field, which block both movement and tags.
+    pub obstacles: Vec<Obstacle>,
+
+    /// The radius of each player's body, which other players can't overlap; 0 lets players pass through each other.
+    /// This is separate from `proximity`, the reach for tagging.
+    pub body_radius: f64,
+
+    /// How overlapping bodies are dealt with.
+    pub collisions: Collisions,
+
+    /// How players' controls turn into movement.
+    pub motion: Motion,
+
+    /// The maximum distance per turn of a sprinting player/agent.
+    pub sprint_speed: f64,
+
+    /// The stamina each player starts with and can hold at most; sprinting uses up however far the sprint goes beyond `speed`.
+    pub stamina: f64,
+
+    /// The stamina a player recovers in each step they move without sprinting.
+    pub recovery: f64,
+
+    /// Whether the game is played in steps or in continuous time.
+    pub time_mode: TimeMode,
+
+    /// The order in which players act within a step of a stepped game.
+    pub schedule: Schedule,
+
+    /// How long, in milliseconds, each agent has to decide on an action; 0 gives them as long as they need.
+    pub budget: f64,
+
+    /// What an agent that runs out of time does instead.
+    pub fallback: Fallback,
+
+    /// What ends the game; with none, it runs until it's stopped.
+    pub end: Vec<EndCondition>,
+
+    /// The game being played.
+    pub mode: GameMode,
+
+    /// What happens to a player tagged in team tag or capture the flag.
+    pub capture: Capture,
+
+    /// The number of flags a team has to capture to win capture the flag; 0 leaves the game to be won by no one.
+    pub flags_to_win: usize,
+
 }
 
 const SPEED: f64 = 5.0;
@@ -35,14 +122,54 @@ const WIDTH: u64 = 1000;
 const HEIGHT: u64 = 600;
 const NUM_PLAYERS: usize = 50;
 const NUM_IT: usize = 1;
+const SEED: u64 = 0;
+const THREADS: usize = 0;
+const CONFLICT_POLICY: ConflictPolicy = ConflictPolicy::ClosestTagger;
+const BOUNDARY: Boundary = Boundary::Clamp;
+const BODY_RADIUS: f64 = 0.0;
+const COLLISIONS: Collisions = Collisions::Push;
+const SPRINT_SPEED: f64 = 8.0;
+const STAMINA: f64 = 0.0;
+const RECOVERY: f64 = 1.0;
+const TIME_MODE: TimeMode = TimeMode::Stepped;
+const SCHEDULE: Schedule = Schedule::Simultaneous;
+const MODE: GameMode = GameMode::Classic;
+const CAPTURE: Capture = Capture::Jail;
+const FLAGS_TO_WIN: usize = 3;
+const BUDGET: f64 = 0.0;
+const FALLBACK: Fallback = Fallback::StandStill;
 
 pub(crate) const DEFAULT_PARAMS: TagParams = TagParams {
-    speed: SPEED,
-    proximity: PROXIMITY,
+    speed: Distribution::Fixed(SPEED),
+    proximity: Distribution::Fixed(PROXIMITY),
+    it_speed: None,
+    it_proximity: None,
+    roster: BTreeMap::new(),
     width: WIDTH,
     height: HEIGHT,
     num_players: NUM_PLAYERS,
     agent_type: AgentType::Default,
+    population: Population(Vec::new()),
     numbered: false,
     num_it: NUM_IT,
+    seed: SEED,
+    threads: THREADS,
+    conflict_policy: CONFLICT_POLICY,
+    rules: DEFAULT_RULES,
+    boundary: BOUNDARY,
+    obstacles: Vec::new(),
+    body_radius: BODY_RADIUS,
+    collisions: COLLISIONS,
+    motion: DEFAULT_MOTION,
+    sprint_speed: SPRINT_SPEED,
+    stamina: STAMINA,
+    recovery: RECOVERY,
+    time_mode: TIME_MODE,
+    schedule: SCHEDULE,
+    budget: BUDGET,
+    fallback: FALLBACK,
+    end: Vec::new(),
+    mode: MODE,
+    capture: CAPTURE,
+    flags_to_win: FLAGS_TO_WIN,
 };
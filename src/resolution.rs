@@ -0,0 +1,58 @@
+use crate::action::Rejection;
+use std::str::FromStr;
+
+/// How the environment picks a winner when several 'it's tag the same player in one step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictPolicy {
+    /// The tagger nearest the target wins, with ties going to the lower id.
+    ClosestTagger,
+    /// A tagger picked at random (from the simulation's seeded generator) wins.
+    Random,
+    /// The tagger with the lowest id wins.
+    EarliestIndex,
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "closest" | "closest_tagger" => Ok(ConflictPolicy::ClosestTagger),
+            "random" => Ok(ConflictPolicy::Random),
+            "earliest" | "earliest_index" => Ok(ConflictPolicy::EarliestIndex),
+            _ => Err(format!("Unknown conflict policy {:?}.", value)),
+        }
+    }
+}
+
+/// A clash between actions taken in the same step, and how it was settled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conflict {
+    /// Several 'it's tagged the same player; only the winner's tag was applied.
+    SharedTarget { target: usize, winner: usize, losers: Vec<usize> },
+    /// A player was tagged in the same step they tried to move or pick up a flag, so the move or pick-up was dropped.
+    CaughtMoving { target: usize, tagger: usize },
+    /// Several players went for the same flag; only the winner picked it up.
+    SharedFlag { team: usize, winner: usize, losers: Vec<usize> },
+}
+
+/// What happened when a step's actions were applied.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StepReport {
+    /// Actions refused by the environment, and why.
+    pub rejections: Vec<(usize, Rejection)>,
+    /// Conflicts between actions that were resolved.
+    pub conflicts: Vec<Conflict>,
+    /// Tags that missed, as (tagger, target).
+    pub misses: Vec<(usize, usize)>,
+    /// Players who left the game by going out of bounds.
+    pub eliminated: Vec<usize>,
+    /// Tags that landed, as (tagger, target).
+    pub tags: Vec<(usize, usize)>,
+    /// Frozen players set free by a teammate, as (rescuer, rescued).
+    pub rescues: Vec<(usize, usize)>,
+    /// Flags carried home in capture the flag, as (carrier, the flag's team).
+    pub captures: Vec<(usize, usize)>,
+    /// How much game time passed.
+    pub elapsed: f32,
+}
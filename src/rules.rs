@@ -0,0 +1,83 @@
+use std::str::FromStr;
+
+/// How the distance between a tagger and their target is judged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitTest {
+    /// The target's centre must be within the tagger's reach.
+    Center,
+    /// The tagger's and target's circles (of radius `reach`) must overlap.
+    Overlap,
+}
+
+impl FromStr for HitTest {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "center" | "centre" => Ok(HitTest::Center),
+            "overlap" => Ok(HitTest::Overlap),
+            _ => Err(format!("Unknown hit test {:?}.", value)),
+        }
+    }
+}
+
+/// The rules of tagging, enforced by the environment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TagRules {
+
+    /// The number of steps after being tagged during which a player may not tag back whoever tagged them.
+    /// `u64::MAX` never allows tag-backs.
+    pub tag_back_cooldown: u64,
+
+    /// The number of steps after passing 'it' on during which a player can't be tagged.
+    pub immunity: u64,
+
+    /// The chance of a tag missing when the target is right next to the tagger.
+    pub miss_near: f64,
+
+    /// The chance of a tag missing when the target is at the edge of the tagger's range;
+    /// the chance in between is interpolated by distance.
+    pub miss_far: f64,
+
+    /// How the tagger's range is measured.
+    pub hit_test: HitTest,
+
+}
+
+impl TagRules {
+
+    /// The chance of a tag missing at `distance` when the tagger's range is `range`.
+    pub fn miss_chance(&self, distance: f32, range: f32) -> f64 {
+        let fraction = if range > 0. { ((distance / range) as f64).clamp(0., 1.) } else { 1. };
+        (self.miss_near + (self.miss_far - self.miss_near) * fraction).clamp(0., 1.)
+    }
+
+}
+
+/// Classic playground rules: no tag-backs ever, no immunity and no missed tags.
+pub(crate) const DEFAULT_RULES: TagRules = TagRules {
+    tag_back_cooldown: u64::MAX,
+    immunity: 0,
+    miss_near: 0.0,
+    miss_far: 0.0,
+    hit_test: HitTest::Center,
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::{DEFAULT_RULES, TagRules};
+
+    #[test]
+    fn interpolates_miss_chance() {
+        let rules = TagRules {
+            miss_near: 0.1,
+            miss_far: 0.5,
+            ..DEFAULT_RULES
+        };
+        assert!((rules.miss_chance(0., 10.) - 0.1).abs() < 1e-6);
+        assert!((rules.miss_chance(5., 10.) - 0.3).abs() < 1e-6);
+        assert!((rules.miss_chance(20., 10.) - 0.5).abs() < 1e-6);
+        assert_eq!(0., DEFAULT_RULES.miss_chance(5., 10.));
+    }
+
+}
@@ -0,0 +1,117 @@
+use crate::action::Action;
+use crate::agents::agent::Agent;
+use crate::simulation::agent_rng;
+use crate::tag_environment::TagEnvironment;
+use async_std::channel::{unbounded, Receiver, Sender};
+use async_std::future;
+use async_std::sync::Arc;
+use async_std::task;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// How long a step waits for the first action to arrive before going ahead without any.
+const PATIENCE: Duration = Duration::from_millis(10);
+
+/// A snapshot of the game, with the time it was sent.
+type Snapshot<X> = (Instant, Arc<TagEnvironment<X>>);
+
+/// The agents of an asynchronous game, each running as its own task.
+/// Agents are sent a snapshot of the game whenever they're ready for one, and send back an action once
+/// they've decided on it; the game applies actions in the order they arrive.
+/// Each snapshot goes out with the time it was sent, so that an action comes back with how long it took.
+pub(crate) struct Runtime<X>
+    where
+        X: Agent + 'static
+{
+    /// Where to send each agent's snapshots; an agent's task ends when its sender is dropped.
+    snapshots: HashMap<usize, Sender<Snapshot<X>>>,
+    actions: Receiver<(usize, Action, Instant)>,
+    /// Handed to each new task for sending its actions back, along with when the snapshot it acted on was sent.
+    returns: Sender<(usize, Action, Instant)>,
+    /// The agents waiting for a snapshot, having acted on the last one they were sent.
+    ready: HashSet<usize>,
+    seed: u64,
+}
+
+impl<X: Agent + 'static> Runtime<X> {
+
+    pub fn new(seed: u64) -> Self {
+        let (returns, actions) = unbounded();
+        Runtime {
+            snapshots: HashMap::new(),
+            actions,
+            returns,
+            ready: HashSet::new(),
+            seed,
+        }
+    }
+
+    /// Sends every agent that's ready a snapshot of the game as it stands, starting tasks for agents that have joined
+    /// and ending those of agents that have left. The game is only copied when some agent is ready for it.
+    pub fn publish(&mut self, environment: &TagEnvironment<X>) {
+        self.snapshots.retain(|agent, _| environment.agents.contains_key(agent));
+        let snapshots = &self.snapshots;
+        self.ready.retain(|agent| snapshots.contains_key(agent));
+        let mut joined: Vec<usize> = environment.agents.iter()
+            .map(|agent| *agent.key())
+            .filter(|agent| !self.snapshots.contains_key(agent))
+            .collect();
+        joined.sort_unstable();
+        for agent in joined {
+            self.spawn(agent);
+        }
+        if self.ready.is_empty() {
+            return;
+        }
+        let snapshot = Arc::new(environment.clone());
+        let sent = Instant::now();
+        for agent in self.ready.drain() {
+            // a task that has stopped is tidied away once its agent leaves
+            let _ = self.snapshots[&agent].try_send((sent, snapshot.clone()));
+        }
+    }
+
+    /// Waits a little while for at least one action, then takes every other action that has arrived too,
+    /// in the order they came in, each with the time from sending the snapshot it was decided on to receiving it.
+    /// If no agent is ready in time there are no actions, so a stalled agent can't hold up the game.
+    pub fn collect(&mut self) -> Vec<(usize, Action, Duration)> {
+        if self.snapshots.is_empty() {
+            return Vec::new();
+        }
+        let mut actions = Vec::new();
+        if let Ok(Ok((agent, action, sent))) = task::block_on(future::timeout(PATIENCE, self.actions.recv())) {
+            actions.push((agent, action, sent.elapsed()));
+        }
+        while let Ok((agent, action, sent)) = self.actions.try_recv() {
+            actions.push((agent, action, sent.elapsed()));
+        }
+        self.ready.extend(actions.iter().map(|(agent, _, _)| *agent));
+        actions
+    }
+
+    fn spawn(&mut self, agent: usize) {
+        let (sender, snapshots) = unbounded::<Snapshot<X>>();
+        let returns = self.returns.clone();
+        let mut rng = agent_rng(self.seed, agent);
+        task::spawn(async move {
+            while let Ok((sent, snapshot)) = snapshots.recv().await {
+                let me = match snapshot.agents.get(&agent) {
+                    None => break,
+                    Some(me) => *me,
+                };
+                // deciding can take a while, so it's kept off the executor's threads
+                let (action, returned) = task::spawn_blocking(move || {
+                    let action = me.act(&snapshot, &mut rng);
+                    (action, rng)
+                }).await;
+                rng = returned;
+                if returns.send((agent, action, sent)).await.is_err() {
+                    break;
+                }
+            }
+        });
+        self.snapshots.insert(agent, sender);
+        self.ready.insert(agent);
+    }
+
+}
@@ -0,0 +1,46 @@
+use std::str::FromStr;
+
+/// The order in which players act within a step of a stepped game.
+/// Every schedule takes the same one step per round of actions, so results are comparable across schedules.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Schedule {
+    /// Everyone chooses against the same state, and all the actions are resolved at once.
+    Simultaneous,
+    /// Everyone chooses against the same state, then the actions are applied one at a time in a fresh random order
+    /// each step, so an action may no longer be allowed by the time its turn comes.
+    RandomOrder,
+    /// As many times as there are players, a player picked at random chooses against the current state and acts
+    /// straight away; some players may act several times in a step, and others not at all.
+    RandomActivation,
+    /// The 'it's choose and act all at once, then the runners choose against the state that left and act all at once.
+    Alternating,
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "simultaneous" => Ok(Schedule::Simultaneous),
+            "random_order" | "shuffled" => Ok(Schedule::RandomOrder),
+            "random_activation" => Ok(Schedule::RandomActivation),
+            "alternating" => Ok(Schedule::Alternating),
+            _ => Err(format!("Unknown schedule {:?}.", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schedule::Schedule;
+
+    #[test]
+    fn parses_schedules() {
+        assert_eq!(Ok(Schedule::Simultaneous), "simultaneous".parse());
+        assert_eq!(Ok(Schedule::RandomOrder), "Shuffled".parse());
+        assert_eq!(Ok(Schedule::RandomActivation), "random_activation".parse());
+        assert_eq!(Ok(Schedule::Alternating), "alternating".parse());
+        assert!("sequential".parse::<Schedule>().is_err());
+    }
+
+}
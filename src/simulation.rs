@@ -1,12 +1,27 @@
 use crate::environment::Environment;
 use crate::parameters::{TagParams};
 use crate::tag_environment::TagEnvironment;
-use dashmap::DashMap;
 use crate::action::Action;
 use crate::controls::Controls;
 use iced::{Application, Settings, window};
 use crate::agents::agent::Agent;
-use std::collections::HashSet;
+use crate::resolution::StepReport;
+use rand::rngs::StdRng;
+use rand::{SeedableRng, Rng};
+use rand::seq::SliceRandom;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use rayon::prelude::*;
+use crate::continuous::TimeMode;
+use crate::schedule::Schedule;
+use crate::runtime::Runtime;
+use crate::latency::{Latency, Fallback};
+use crate::outcome::{EndCondition, EndReason, GameOutcome, PlayerResult, Elimination};
+use crate::infection::{Epidemic, Infection};
+use crate::game_mode::GameMode;
+use iced::Vector;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// The main tag simulation instance.
 ///
@@ -14,7 +29,7 @@ use std::collections::HashSet;
 /// ## Standalone (no UI)
 /// ```
 ///     let simulation: Simulation = Simulation::new(DEFAULT_PARAMS);
-///     simulation.run_headless(Option::from(500));
+///     let outcome: GameOutcome = simulation.run_headless(Option::from(500));
 /// ```
 ///
 /// ## WIth Iced GUI
@@ -29,28 +44,72 @@ pub struct Simulation<X>
     pub(crate) environment: TagEnvironment<X>,
     pub(crate) is_running: bool,
     pub(crate) controls: Controls,
+    /// The single source of randomness for the agents and environment, seeded from the parameters.
+    pub(crate) rng: StdRng,
+    /// The threads agents choose their actions on.
+    pub(crate) pool: ThreadPool,
+    /// The agents' own tasks, once an asynchronous game has started.
+    pub(crate) runtime: Option<Runtime<X>>,
+    /// How long each agent has taken to decide on its actions.
+    pub(crate) latencies: HashMap<usize, Latency>,
+    /// The agents still working on a decision they ran out of time for.
+    pub(crate) deciding: Arc<Mutex<HashSet<usize>>>,
+    /// Each agent's last move, for repeating when it runs out of time.
+    pub(crate) last_moves: HashMap<usize, Action>,
+    /// How each player who has taken part since the last reset is doing.
+    pub(crate) results: BTreeMap<usize, PlayerResult>,
+    /// When the first step since the last reset was taken, for the time limit.
+    pub(crate) started: Option<Instant>,
+    /// How the infection has spread since the last reset, in infection tag.
+    pub(crate) epidemic: Epidemic,
+    /// The players eliminated since the last reset, in order.
+    pub(crate) eliminations: Vec<Elimination>,
 }
 
 impl<X: Agent + 'static> Simulation<X> {
 
     pub fn new(parameters: TagParams) -> Self {
         let mut sim = Simulation {
-                    parameters,
-                    environment: TagEnvironment {
-                        agents: DashMap::with_capacity(parameters.speed as usize),
-                        width: parameters.width as f32,
-                        height: parameters.height as f32,
-                        it: HashSet::new(),
-                        show_numbers: parameters.numbered,
-                    },
+                    environment: TagEnvironment::new(&parameters),
                     is_running: false,
                     controls: Controls::default(),
+                    rng: StdRng::seed_from_u64(parameters.seed),
+                    pool: ThreadPoolBuilder::new()
+                        .num_threads(parameters.threads)
+                        .build()
+                        .expect("Unable to start the agents' thread pool."),
+                    runtime: None,
+                    latencies: HashMap::new(),
+                    deciding: Arc::new(Mutex::new(HashSet::new())),
+                    last_moves: HashMap::new(),
+                    results: BTreeMap::new(),
+                    started: None,
+                    epidemic: Epidemic::default(),
+                    eliminations: Vec::new(),
+                    parameters,
                 };
-                sim.environment.reset(parameters);
+                sim.environment.reset(&sim.parameters, &mut sim.rng);
+                sim.track_players(0.);
+                sim.track_infections(&[]);
                 sim
 
     }
 
+    /// Restarts the game from the beginning, re-seeding so that the same game is replayed.
+    pub fn reset(&mut self) {
+        self.runtime = None;
+        self.latencies.clear();
+        self.last_moves.clear();
+        self.results.clear();
+        self.started = None;
+        self.epidemic = Epidemic::default();
+        self.eliminations.clear();
+        self.rng = StdRng::seed_from_u64(self.parameters.seed);
+        self.environment.reset(&self.parameters, &mut self.rng);
+        self.track_players(0.);
+        self.track_infections(&[]);
+    }
+
     pub fn run_gui(parameters: TagParams) {
         let window = window::Settings {
             size: (parameters.width as u32, parameters.height as u32),
@@ -66,83 +125,975 @@ impl<X: Agent + 'static> Simulation<X> {
         Simulation::<X>::run(settings);
     }
 
-    pub fn run_headless(&mut self, num_steps: Option<u128>) {
-        match num_steps {
-            None => {
-                self.is_running = true;
-                while self.is_running {
-                    self.step();
-                }
+    /// Plays until an end condition is met, `num_steps` steps have been taken or the game is stopped,
+    /// and returns how it ended.
+    #[allow(dead_code)]
+    pub fn run_headless(&mut self, num_steps: Option<u128>) -> GameOutcome {
+        self.is_running = true;
+        let mut taken: u128 = 0;
+        let reason = loop {
+            if let Some(reason) = self.ended() {
+                break reason;
             }
-            Some(steps) => {
-                self.is_running = true;
-                for _ in 0..steps {
-                    self.step();
-                }
-                self.is_running = false;
+            if num_steps.map_or(false, |steps| taken >= steps) {
+                break EndReason::StepLimit;
+            }
+            if !self.is_running {
+                break EndReason::Stopped;
             }
+            self.step();
+            taken += 1;
+        };
+        self.is_running = false;
+        self.log_latencies();
+        let outcome = self.outcome(reason);
+        log::info!("The game ended after {:?} steps ({:?}); the winners are {:?}.", outcome.steps, reason, outcome.winners());
+        if let Some(epidemic) = &outcome.epidemic {
+            self.log_epidemic(epidemic);
+        }
+        if !outcome.eliminations.is_empty() {
+            log::info!("The players ranked by how long they survived: {:?}.", outcome.ranking());
+        }
+        if let Some(scores) = &outcome.scores {
+            log::info!("The flags captured by each team: {:?}.", scores);
+        }
+        outcome
+    }
+
+    /// Why the game is over, if one of the end conditions has been met; the first met, in the order given, counts.
+    /// An infection tag game is over once everyone is infected, and an elimination tag game once it's won,
+    /// whatever the end conditions.
+    pub fn ended(&self) -> Option<EndReason> {
+        match self.environment.mode {
+            GameMode::Infection if self.environment.winners().is_some() => return Some(EndReason::AllInfected),
+            GameMode::Elimination if self.environment.winners().is_some() => return Some(EndReason::Won),
+            _ => {}
+        }
+        self.parameters.end.iter().find_map(|condition| match condition {
+            EndCondition::Steps(steps) if self.environment.steps >= *steps => Some(EndReason::StepLimit),
+            EndCondition::Time(seconds) if self.started.map_or(false, |started| started.elapsed().as_secs_f64() >= *seconds) => Some(EndReason::TimeLimit),
+            EndCondition::EveryoneIt if self.environment.agents.iter().all(|agent| self.results.get(agent.key()).map_or(false, |result| result.been_it)) => Some(EndReason::EveryoneIt),
+            EndCondition::Won if self.environment.winners().is_some() => Some(EndReason::Won),
+            _ => None,
+        })
+    }
+
+    /// How the game stands, taken as having ended for the given reason.
+    /// A game that hasn't been won goes to whoever's ahead when it ends.
+    pub fn outcome(&self, reason: EndReason) -> GameOutcome {
+        let winners = match reason {
+            EndReason::Won | EndReason::AllInfected => self.environment.winners().unwrap_or_default(),
+            _ => self.environment.leaders(),
+        };
+        GameOutcome {
+            reason,
+            steps: self.environment.steps,
+            time: self.environment.time,
+            players: self.results.values()
+                .map(|result| PlayerResult {
+                    is_it: self.environment.it.contains(&result.id),
+                    in_game: self.environment.agents.contains_key(&result.id),
+                    won: winners.contains(&result.id),
+                    violations: self.environment.violations(result.id),
+                    ..*result
+                })
+                .collect(),
+            epidemic: (self.environment.mode == GameMode::Infection).then(|| self.epidemic.clone()),
+            eliminations: self.eliminations.clone(),
+            scores: (self.environment.mode == GameMode::Flag).then(|| self.environment.scores.to_vec()),
+        }
+    }
+
+    /// Adds up the tags, rescues and flag captures made in a step, who spent it as 'it', and who was taken out of the game.
+    fn record(&mut self, report: &StepReport) {
+        for (tagger, target) in &report.tags {
+            self.results.entry(*tagger).or_insert_with(|| PlayerResult { id: *tagger, ..PlayerResult::default() }).tags += 1;
+            self.results.entry(*target).or_insert_with(|| PlayerResult { id: *target, ..PlayerResult::default() }).times_tagged += 1;
+        }
+        for (rescuer, _) in &report.rescues {
+            self.results.entry(*rescuer).or_insert_with(|| PlayerResult { id: *rescuer, ..PlayerResult::default() }).rescues += 1;
+        }
+        for (carrier, _) in &report.captures {
+            self.results.entry(*carrier).or_insert_with(|| PlayerResult { id: *carrier, ..PlayerResult::default() }).captures += 1;
+        }
+        self.track_players(report.elapsed as f64);
+        self.track_infections(&report.tags);
+        self.track_eliminations(report);
+    }
+
+    /// Adds the players taken out of the game in a step to the order of elimination.
+    fn track_eliminations(&mut self, report: &StepReport) {
+        let (step, time) = (self.environment.steps, self.environment.time);
+        if self.environment.mode == GameMode::Elimination {
+            self.eliminations.extend(report.tags.iter().map(|(by, player)| Elimination { player: *player, by: Some(*by), step, time }));
+        }
+        self.eliminations.extend(report.eliminated.iter().map(|player| Elimination { player: *player, by: None, step, time }));
+    }
+
+    /// In infection tag, adds a step's infections to the tree, and the number infected now to the curve;
+    /// the tree starts from whoever is infected when it's empty.
+    fn track_infections(&mut self, tags: &[(usize, usize)]) {
+        if self.environment.mode != GameMode::Infection {
+            return;
+        }
+        if self.epidemic.infections.is_empty() {
+            let mut its: Vec<usize> = self.environment.it.iter().copied().collect();
+            its.sort_unstable();
+            self.epidemic.infections.extend(its.into_iter().map(|infected| Infection { infected, by: None, step: 0 }));
         }
+        let step = self.environment.steps;
+        self.epidemic.infections.extend(tags.iter().map(|(by, infected)| Infection { infected: *infected, by: Some(*by), step }));
+        self.epidemic.curve.push((self.environment.time, self.environment.it.len()));
     }
 
+    /// Makes sure every player in the game has a result, and credits whoever is 'it' now with the time just passed.
+    fn track_players(&mut self, elapsed: f64) {
+        for agent in self.environment.agents.iter() {
+            let id = *agent.key();
+            let result = self.results.entry(id).or_insert_with(|| PlayerResult { id, ..PlayerResult::default() });
+            result.team = agent.player().team;
+            if self.environment.it.contains(&id) {
+                result.been_it = true;
+                result.time_it += elapsed;
+            }
+        }
+    }
+
+    /// Logs how long each agent has taken over its decisions.
+    fn log_latencies(&self) {
+        let mut agents: Vec<(&usize, &Latency)> = self.latencies.iter().collect();
+        agents.sort_unstable_by_key(|(agent, _)| **agent);
+        for (agent, latency) in agents {
+            log::info!("Agent {:?} took {:?} per decision on average and {:?} at most, running out of time {:?} times in {:?}.",
+                       agent, latency.mean(), latency.longest, latency.overruns, latency.decisions);
+        }
+    }
+
+    /// Logs who spread the infection furthest.
+    fn log_epidemic(&self, epidemic: &Epidemic) {
+        let spreader = epidemic.infections.iter()
+            .map(|infection| (epidemic.infected_by(infection.infected).len(), infection.infected))
+            .max_by(|(a_count, a), (b_count, b)| a_count.cmp(b_count).then(b.cmp(a)));
+        let generations = epidemic.infections.iter().filter_map(|infection| epidemic.generation(infection.infected)).max();
+        if let (Some((count, spreader)), Some(generations)) = (spreader, generations) {
+            log::info!("The infection spread over {:?} generations; agent {:?} infected the most players ({:?}).", generations, spreader, count);
+        }
+    }
+
+    /// Brings a new player into the game mid-way, returning their id.
+    pub fn join(&mut self) -> usize {
+        let id = self.environment.next_id();
+        let agent = X::create(id, &self.parameters, &mut self.rng);
+        self.environment.add_agent(agent);
+        id
+    }
+
+    /// Takes a player out of the game mid-way, returning whether they were in it.
+    pub fn leave(&mut self, agent: usize) -> bool {
+        self.environment.remove_agent(agent).is_some()
+    }
+
+    #[allow(dead_code)]
     pub fn stop(&mut self) {
         self.is_running = false;
     }
 
-    pub(crate) fn step(&mut self) {
-        // TODO something like a countdown latch here or Rayon iters, or abandon turn-based altogether and have agents in their own threads.
-        let mut actions: Vec<Action> = Vec::with_capacity(self.environment.agents.len());
-        for agent in 0..self.environment.agents.len() {
-            actions.insert(agent, self.environment.agents.get(&agent).unwrap().act(&self.environment));
+    /// Has every agent choose an action against the same state, in parallel, then applies them,
+    /// either as one turn, in the order the schedule sets, or by running on in continuous time for a unit of time.
+    /// Each agent draws from its own random stream for the step, so the actions chosen don't depend on
+    /// the number of threads or the order the agents happen to run in.
+    /// In an asynchronous game, the agents' own tasks are sent the latest state instead, and whichever actions
+    /// have come back are applied in the order they arrived.
+    pub(crate) fn step(&mut self) -> StepReport {
+        self.started.get_or_insert_with(Instant::now);
+        let report = match self.parameters.time_mode {
+            TimeMode::Stepped => self.step_on_schedule(),
+            TimeMode::Continuous => {
+                let actions = self.choose_actions(self.current_agents());
+                self.environment.advance(actions, DECISION_INTERVAL, &mut self.rng)
+            }
+            TimeMode::Asynchronous => {
+                let seed = self.parameters.seed;
+                let budget = self.budget();
+                let runtime = self.runtime.get_or_insert_with(|| Runtime::new(seed));
+                runtime.publish(&self.environment);
+                // actions arrive whenever they're ready, so one that took too long is only dropped once it's here
+                let decisions = runtime.collect().into_iter()
+                    .map(|(agent, action, latency)| match budget {
+                        Some(budget) if latency > budget => (agent, None, latency),
+                        _ => (agent, Some(action), latency),
+                    })
+                    .collect();
+                let actions = self.settle(decisions);
+                self.environment.step_in_order(actions, &mut self.rng)
+            }
+        };
+        self.record(&report);
+        report
+    }
+
+    /// Plays one step of a stepped game, with agents choosing and acting in the order the schedule sets.
+    fn step_on_schedule(&mut self) -> StepReport {
+        let mut report = StepReport::default();
+        match self.parameters.schedule {
+            Schedule::Simultaneous => {
+                let actions = self.choose_actions(self.current_agents());
+                return self.environment.step_all(actions, &mut self.rng);
+            }
+            Schedule::RandomOrder => {
+                let mut actions = self.choose_actions(self.current_agents());
+                actions.shuffle(&mut self.rng);
+                for (agent, action) in actions {
+                    self.environment.activate(agent, action, &mut self.rng, &mut report);
+                }
+            }
+            Schedule::RandomActivation => {
+                for _ in 0..self.environment.agents.len() {
+                    let agents = self.current_agents();
+                    if agents.is_empty() {
+                        break;
+                    }
+                    let agent = agents[self.rng.gen_range(0, agents.len())];
+                    for (agent, action) in self.choose_actions(vec![agent]) {
+                        self.environment.activate(agent, action, &mut self.rng, &mut report);
+                    }
+                }
+            }
+            Schedule::Alternating => {
+                let (its, runners): (Vec<usize>, Vec<usize>) = self.current_agents().into_iter()
+                    .partition(|agent| self.environment.it.contains(agent));
+                let actions = self.choose_actions(its);
+                self.environment.resolve(actions, &mut self.rng, &mut report);
+                let runners = runners.into_iter()
+                    .filter(|agent| self.environment.agents.contains_key(agent) && !self.environment.it.contains(agent))
+                    .collect();
+                let actions = self.choose_actions(runners);
+                self.environment.resolve(actions, &mut self.rng, &mut report);
+            }
+        }
+        self.environment.end_step(&mut report);
+        report
+    }
+
+    /// The ids of the agents in the game, in order.
+    fn current_agents(&self) -> Vec<usize> {
+        let mut agents: Vec<usize> = self.environment.agents.iter().map(|agent| *agent.key()).collect();
+        agents.sort_unstable();
+        agents
+    }
+
+    /// The actions of the given agents, in order of id, recording how long each took to decide.
+    /// Agents that don't decide within the budget, if there is one, fall back on a default action.
+    fn choose_actions(&mut self, agents: Vec<usize>) -> Vec<(usize, Action)> {
+        let step_seed: u64 = self.rng.gen();
+        let decisions = match self.budget() {
+            Some(budget) => self.decide_within_budget(agents, step_seed, budget),
+            None => {
+                let environment = &self.environment;
+                self.pool.install(|| {
+                    agents.into_par_iter()
+                        .map(|agent| {
+                            let start = Instant::now();
+                            let action = environment.agents.get(&agent).unwrap().act(environment, &mut agent_rng(step_seed, agent));
+                            (agent, Some(action), start.elapsed())
+                        })
+                        .collect()
+                })
+            }
+        };
+        self.settle(decisions)
+    }
+
+    /// Has every agent decide on a snapshot of the game on the agents' threads, waiting no longer than the budget.
+    /// An agent still deciding when time runs out finishes in the background and its decision is dropped;
+    /// it isn't asked again until it has finished, so a slow agent never ties up more than one thread.
+    fn decide_within_budget(&self, agents: Vec<usize>, step_seed: u64, budget: Duration) -> Vec<(usize, Option<Action>, Duration)> {
+        let deadline = Instant::now() + budget;
+        let snapshot = Arc::new(self.environment.clone());
+        let (sender, receiver) = mpsc::channel();
+        let mut waiting = 0;
+        for &agent in &agents {
+            if !self.deciding.lock().unwrap().insert(agent) {
+                continue;
+            }
+            waiting += 1;
+            let (snapshot, sender, deciding) = (snapshot.clone(), sender.clone(), self.deciding.clone());
+            self.pool.spawn(move || {
+                let start = Instant::now();
+                let action = snapshot.agents.get(&agent).unwrap().act(&snapshot, &mut agent_rng(step_seed, agent));
+                deciding.lock().unwrap().remove(&agent);
+                // once the step has gone ahead without it, nobody is listening
+                let _ = sender.send((agent, action, start.elapsed()));
+            });
+        }
+        let mut decided: HashMap<usize, (Action, Duration)> = HashMap::with_capacity(waiting);
+        while decided.len() < waiting {
+            match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok((agent, action, latency)) => { decided.insert(agent, (action, latency)); }
+                Err(_) => break,
+            }
+        }
+        agents.into_iter()
+            .map(|agent| match decided.remove(&agent) {
+                Some((action, latency)) => (agent, Some(action), latency),
+                None => (agent, None, budget),
+            })
+            .collect()
+    }
+
+    /// How long each agent has to decide, if there's a limit.
+    fn budget(&self) -> Option<Duration> {
+        if self.parameters.budget > 0. {
+            Some(Duration::from_secs_f64(self.parameters.budget / 1000.))
+        } else {
+            None
+        }
+    }
+
+    /// Records how long each decision took, falling back on a default action for agents that didn't decide in time.
+    fn settle(&mut self, decisions: Vec<(usize, Option<Action>, Duration)>) -> Vec<(usize, Action)> {
+        decisions.into_iter()
+            .map(|(agent, action, latency)| {
+                self.latencies.entry(agent).or_default().record(latency, action.is_none());
+                let action = match action {
+                    Some(action) => action,
+                    None => {
+                        log::warn!("Agent {:?} ran out of time to decide.", agent);
+                        self.fallback(agent)
+                    }
+                };
+                if let Some(repeat) = self.as_repeated(agent, action) {
+                    self.last_moves.insert(agent, repeat);
+                }
+                (agent, action)
+            })
+            .collect()
+    }
+
+    /// The action that carries on a move, if it is one: a move to a point becomes steering at the velocity it took,
+    /// since moving to the same point again wouldn't keep going the same way.
+    fn as_repeated(&self, agent: usize, action: Action) -> Option<Action> {
+        match action {
+            Action::Move(target) => self.environment.agents.get(&agent).map(|me| {
+                let [x, y] = self.environment.arena.delta(me.player().position, target);
+                Action::Steer(Vector::new(x, y))
+            }),
+            Action::Steer(_) | Action::Sprint(_) => Some(action),
+            _ => None,
+        }
+    }
+
+    /// What an agent that ran out of time does instead.
+    fn fallback(&self, agent: usize) -> Action {
+        match (self.parameters.fallback, self.last_moves.get(&agent)) {
+            (Fallback::Repeat, Some(action)) => *action,
+            _ => Action::Steer(Vector::new(0., 0.)),
         }
-        &self.environment.step_all(actions);
     }
 
 }
 
+/// How much game time passes between choices in a continuous-time game.
+const DECISION_INTERVAL: f32 = 1.0;
+
+/// The random stream for one agent's decision in one step.
+pub(crate) fn agent_rng(step_seed: u64, agent: usize) -> StdRng {
+    let mut seed = <StdRng as SeedableRng>::Seed::default();
+    seed[..8].copy_from_slice(&step_seed.to_le_bytes());
+    seed[8..16].copy_from_slice(&(agent as u64).to_le_bytes());
+    StdRng::from_seed(seed)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::parameters::TagParams;
+    use crate::parameters::{TagParams, DEFAULT_PARAMS};
     use crate::simulation::Simulation;
     use crate::agents::agent_type::AgentType;
     use crate::agents::agent::{Player};
     use crate::agents::basic_directional::DirectionalAgent;
+    use crate::agents::mixed::MixedAgent;
+    use crate::arena::Boundary;
+    use crate::collisions::Collisions;
+    use crate::motion::{Motion, MotionModel, DEFAULT_MOTION};
+    use crate::attributes::{Distribution, Attributes};
+    use crate::continuous::TimeMode;
+    use crate::schedule::Schedule;
+    use crate::resolution::StepReport;
+    use crate::latency::Fallback;
+    use crate::runtime::Runtime;
+    use crate::outcome::{EndCondition, EndReason};
+    use crate::game_mode::{GameMode, Capture};
+    use crate::agents::agent::Agent;
+    use crate::tag_environment::TagEnvironment;
+    use crate::action::Action;
+    use iced::Vector;
+    use rand::rngs::StdRng;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use iced::Point;
     use test::Bencher;
 
     #[test]
     fn test_basic_functionality() {
         let params: TagParams = TagParams {
-            speed: 10.0,
-            proximity: 2.0,
+            speed: Distribution::Fixed(10.0),
+            proximity: Distribution::Fixed(2.0),
             width: 100,
             height: 100,
             num_players: 5,
             agent_type: AgentType::Default,
             numbered: false,
-            num_it: 1
+            num_it: 1,
+            ..DEFAULT_PARAMS
         };
-        let mut sim: Simulation<Player> = Simulation::new(params);
-        assert_eq!(sim.is_running, false);
-        assert_eq!(sim.environment.height, 100.);
-        assert_eq!(sim.environment.width, 100.);
+        let mut sim: Simulation<Player> = Simulation::new(params.clone());
+        assert!(!sim.is_running);
+        assert_eq!(sim.environment.arena.height, 100.);
+        assert_eq!(sim.environment.arena.width, 100.);
         assert_eq!(sim.environment.agents.len(), 5);
-        let agent = sim.environment.agents.get(&0).unwrap().value().clone();
+        let agent = *sim.environment.agents.get(&0).unwrap().value();
         sim.run_headless(Option::from(10));
         assert_ne!(agent.position, sim.environment.agents.get(&0).unwrap().position);
     }
 
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        let params: TagParams = TagParams {
+            num_players: 30,
+            agent_type: AgentType::BasicDirectional,
+            num_it: 3,
+            seed: 42,
+            ..DEFAULT_PARAMS
+        };
+        let mut first: Simulation<DirectionalAgent> = Simulation::new(params.clone());
+        let mut second: Simulation<DirectionalAgent> = Simulation::new(params.clone());
+        for _ in 0..100 {
+            first.step();
+            second.step();
+            assert_eq!(first.environment.it, second.environment.it);
+            for agent in 0..params.num_players {
+                assert_eq!(first.environment.agents.get(&agent).unwrap().value(), second.environment.agents.get(&agent).unwrap().value());
+            }
+        }
+
+        let mut other: Simulation<DirectionalAgent> = Simulation::new(TagParams { seed: 43, ..params.clone() });
+        other.run_headless(Option::from(100));
+        assert!((0..params.num_players).any(|agent| {
+            first.environment.agents.get(&agent).unwrap().value() != other.environment.agents.get(&agent).unwrap().value()
+        }));
+
+        first.reset();
+        let mut replay: Simulation<DirectionalAgent> = Simulation::new(params.clone());
+        first.step();
+        replay.step();
+        for agent in 0..params.num_players {
+            assert_eq!(first.environment.agents.get(&agent).unwrap().value(), replay.environment.agents.get(&agent).unwrap().value());
+        }
+    }
+
+    #[test]
+    fn thread_count_does_not_change_results() {
+        let params: TagParams = TagParams {
+            num_players: 200,
+            agent_type: AgentType::BasicDirectional,
+            num_it: 10,
+            seed: 7,
+            threads: 1,
+            ..DEFAULT_PARAMS
+        };
+        let mut single: Simulation<DirectionalAgent> = Simulation::new(params.clone());
+        let mut multiple: Simulation<DirectionalAgent> = Simulation::new(TagParams { threads: 4, ..params.clone() });
+        single.run_headless(Option::from(50));
+        multiple.run_headless(Option::from(50));
+        assert_eq!(single.environment.it, multiple.environment.it);
+        for agent in 0..params.num_players {
+            assert_eq!(single.environment.agents.get(&agent).unwrap().value(), multiple.environment.agents.get(&agent).unwrap().value());
+        }
+    }
+
+    #[test]
+    fn built_in_agents_follow_the_rules() {
+        let params: TagParams = TagParams {
+            num_players: 100,
+            num_it: 5,
+            ..DEFAULT_PARAMS
+        };
+        let mut default: Simulation<Player> = Simulation::new(params.clone());
+        default.run_headless(Option::from(200));
+        assert!(default.environment.violations.is_empty());
+        let mut directional: Simulation<DirectionalAgent> = Simulation::new(TagParams { agent_type: AgentType::BasicDirectional, ..params.clone() });
+        directional.run_headless(Option::from(200));
+        assert!(directional.environment.violations.is_empty(), "{:?}", directional.environment.violations);
+        for boundary in &[Boundary::Wrap, Boundary::Reflect, Boundary::Eliminate] {
+            let mut bounded: Simulation<DirectionalAgent> = Simulation::new(TagParams { boundary: *boundary, ..params.clone() });
+            bounded.run_headless(Option::from(200));
+            assert!(bounded.environment.violations.is_empty(), "{:?}: {:?}", boundary, bounded.environment.violations);
+            assert_eq!(params.num_players, bounded.environment.agents.len());
+        }
+        for model in &[MotionModel::Momentum, MotionModel::Dubins] {
+            let motion = Motion { model: *model, ..DEFAULT_MOTION };
+            let mut moving: Simulation<DirectionalAgent> = Simulation::new(TagParams { motion, agent_type: AgentType::BasicDirectional, ..params.clone() });
+            moving.run_headless(Option::from(200));
+            assert!(moving.environment.violations.is_empty(), "{:?}: {:?}", model, moving.environment.violations);
+        }
+        let walled = TagParams {
+            obstacles: vec!["circle:500,300,100".parse().unwrap(), "polygon:100,100,400,100,400,120,100,120".parse().unwrap()],
+            ..params.clone()
+        };
+        let mut default: Simulation<Player> = Simulation::new(walled.clone());
+        default.run_headless(Option::from(200));
+        assert!(default.environment.violations.is_empty(), "{:?}", default.environment.violations);
+        let mut directional: Simulation<DirectionalAgent> = Simulation::new(TagParams { agent_type: AgentType::BasicDirectional, ..walled });
+        directional.run_headless(Option::from(200));
+        assert!(directional.environment.violations.is_empty(), "{:?}", directional.environment.violations);
+        for agent in directional.environment.agents.iter() {
+            assert!(!directional.environment.obstacles.iter().any(|obstacle| obstacle.contains(agent.player.position)));
+        }
+    }
+
+    #[test]
+    fn draws_attributes_for_each_player() {
+        let mut params: TagParams = TagParams {
+            num_players: 20,
+            num_it: 3,
+            speed: "uniform:3,7".parse().unwrap(),
+            proximity: "list:10,20".parse().unwrap(),
+            it_speed: Some(Distribution::Fixed(9.)),
+            ..DEFAULT_PARAMS
+        };
+        params.roster.insert(1, Attributes { speed: 1., reach: 1. });
+        let sim: Simulation<Player> = Simulation::new(params);
+        for agent in sim.environment.agents.iter() {
+            let player = *agent.value();
+            if player.id == 1 {
+                assert_eq!((1., 1.), (player.speed, player.reach));
+            } else if player.is_it {
+                assert_eq!(9., player.speed);
+            } else {
+                assert!((3.0..7.0).contains(&player.speed));
+                assert_eq!(if player.id % 2 == 0 { 10. } else { 20. }, player.reach);
+            }
+        }
+        assert_eq!(3, sim.environment.it.len());
+    }
+
+    #[test]
+    fn games_end_on_their_conditions() {
+        let params: TagParams = TagParams {
+            num_players: 20,
+            num_it: 2,
+            agent_type: AgentType::BasicDirectional,
+            end: vec![EndCondition::Steps(30)],
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+        let outcome = sim.run_headless(None);
+        assert_eq!((EndReason::StepLimit, 30, 20), (outcome.reason, outcome.steps, outcome.players.len()));
+        assert_eq!(outcome.players.iter().map(|player| player.tags).sum::<usize>(),
+                   outcome.players.iter().map(|player| player.times_tagged).sum::<usize>());
+        assert_eq!(sim.environment.leaders(), outcome.winners());
+        assert!(outcome.players.iter().all(|player| player.won != player.is_it && player.been_it == (player.time_it > 0.)));
+        assert!(outcome.players.iter().all(|player| player.violations == sim.environment.violations(player.id)));
+        assert_eq!(EndReason::StepLimit, sim.run_headless(Option::from(5)).reason);
+
+        let params: TagParams = TagParams {
+            width: 60,
+            height: 60,
+            num_players: 4,
+            agent_type: AgentType::BasicDirectional,
+            end: vec![EndCondition::EveryoneIt, EndCondition::Steps(2000)],
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+        let outcome = sim.run_headless(None);
+        assert_eq!(EndReason::EveryoneIt, outcome.reason);
+        assert!(outcome.players.iter().all(|player| player.been_it));
+
+        let params: TagParams = TagParams {
+            num_players: 3,
+            end: vec![EndCondition::Won],
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<Player> = Simulation::new(params);
+        sim.run_headless(Option::from(5));
+        sim.leave(0);
+        sim.leave(1);
+        let outcome = sim.run_headless(None);
+        assert_eq!((EndReason::Won, 5, vec![2]), (outcome.reason, outcome.steps, outcome.winners()));
+        assert_eq!(vec![false, false, true], outcome.players.iter().map(|player| player.in_game).collect::<Vec<bool>>());
+    }
+
+    #[test]
+    fn runners_rescue_frozen_teammates() {
+        let params: TagParams = TagParams {
+            width: 400,
+            height: 300,
+            num_players: 30,
+            num_it: 3,
+            agent_type: AgentType::BasicDirectional,
+            mode: GameMode::Freeze,
+            end: vec![EndCondition::Won],
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+        let outcome = sim.run_headless(Option::from(300));
+        assert!(sim.environment.violations.is_empty(), "{:?}", sim.environment.violations);
+        assert_eq!(3, sim.environment.it.len());
+        assert!(outcome.players.iter().any(|player| player.tags > 0 && player.been_it));
+        assert!(outcome.players.iter().any(|player| player.rescues > 0 && !player.been_it));
+        assert!(sim.environment.frozen.iter().all(|frozen| !sim.environment.it.contains(frozen)));
+        let winners = if outcome.reason == EndReason::Won { 3 } else { 30 - 3 - sim.environment.frozen.len() };
+        assert_eq!(winners, outcome.winners().len());
+    }
+
+    #[test]
+    fn infection_spreads_to_everyone() {
+        let params: TagParams = TagParams {
+            width: 300,
+            height: 200,
+            num_players: 20,
+            num_it: 2,
+            agent_type: AgentType::BasicDirectional,
+            mode: GameMode::Infection,
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+        let outcome = sim.run_headless(Option::from(3000));
+        assert_eq!(EndReason::AllInfected, outcome.reason);
+        let epidemic = outcome.epidemic.clone().unwrap();
+        assert_eq!(Some(&(0., 2)), epidemic.curve.first());
+        assert_eq!(Some(&(outcome.time, 20)), epidemic.curve.last());
+        assert!(epidemic.curve.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+        assert_eq!(20, epidemic.infections.len());
+        assert_eq!(2, epidemic.infections.iter().filter(|infection| infection.by.is_none()).count());
+        for (index, infection) in epidemic.infections.iter().enumerate() {
+            if let Some(by) = infection.by {
+                assert!(epidemic.infections[..index].iter().any(|earlier| earlier.infected == by));
+            }
+        }
+        let last = epidemic.infections.last().unwrap().step;
+        let survivors: Vec<usize> = epidemic.infections.iter().filter(|infection| infection.step == last).map(|infection| infection.infected).collect();
+        assert_eq!(survivors.len(), outcome.winners().len());
+        assert!(survivors.iter().all(|survivor| outcome.winners().contains(survivor)));
+    }
+
+    #[test]
+    fn last_runner_standing_wins() {
+        let params: TagParams = TagParams {
+            width: 300,
+            height: 200,
+            num_players: 12,
+            num_it: 2,
+            agent_type: AgentType::BasicDirectional,
+            mode: GameMode::Elimination,
+            schedule: Schedule::RandomActivation,
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+        let outcome = sim.run_headless(Option::from(3000));
+        assert!(sim.environment.violations.is_empty(), "{:?}", sim.environment.violations);
+        assert_eq!(EndReason::Won, outcome.reason);
+        assert_eq!(3, sim.environment.agents.len());
+        assert_eq!(9, outcome.eliminations.len());
+        assert!(outcome.eliminations.iter().all(|elimination| elimination.by.map_or(false, |by| sim.environment.it.contains(&by))));
+        assert!(outcome.eliminations.windows(2).all(|pair| pair[0].time <= pair[1].time));
+        let ranking = outcome.ranking();
+        assert_eq!(12, ranking.len());
+        assert_eq!(outcome.winners(), ranking[..1].to_vec());
+        assert_eq!(outcome.eliminations.first().unwrap().player, *ranking.last().unwrap());
+    }
+
+    #[test]
+    fn teams_play_to_the_rules() {
+        for capture in [Capture::Jail, Capture::Switch] {
+            let params: TagParams = TagParams {
+                width: 400,
+                height: 300,
+                num_players: 30,
+                agent_type: AgentType::Mixed,
+                population: "default:10,directional:20".parse().unwrap(),
+                mode: GameMode::Teams,
+                capture,
+                ..DEFAULT_PARAMS
+            };
+            let mut sim: Simulation<MixedAgent> = Simulation::new(params);
+            assert!(sim.environment.it.is_empty());
+            assert_eq!(15, sim.environment.team_members(1).len());
+            assert!(sim.environment.agents.iter().all(|agent| sim.environment.is_protected(agent.player())));
+            let reports: Vec<StepReport> = (0..300).map(|_| sim.step()).collect();
+            assert!(sim.environment.violations.is_empty(), "{:?}: {:?}", capture, sim.environment.violations);
+            assert!(reports.iter().any(|report| !report.tags.is_empty()), "{:?}", capture);
+            assert_eq!(30, sim.environment.agents.len());
+            if capture == Capture::Jail {
+                let tags: usize = reports.iter().map(|report| report.tags.len()).sum();
+                let rescues: usize = reports.iter().map(|report| report.rescues.len()).sum();
+                assert_eq!(tags - rescues, sim.environment.frozen.len());
+                assert!(sim.environment.frozen.iter().all(|id| {
+                    let player = sim.environment.agents.get(id).unwrap().player();
+                    player.position == sim.environment.jail(1 - player.team)
+                }));
+            } else {
+                assert!(sim.environment.frozen.is_empty());
+                assert_ne!(15, sim.environment.team_members(1).len());
+            }
+        }
+    }
+
+    #[test]
+    fn flags_are_captured() {
+        let params: TagParams = TagParams {
+            width: 400,
+            height: 300,
+            num_players: 20,
+            agent_type: AgentType::BasicDirectional,
+            mode: GameMode::Flag,
+            end: vec![EndCondition::Won, EndCondition::Steps(2000)],
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+        let outcome = sim.run_headless(None);
+        assert_eq!(EndReason::Won, outcome.reason);
+        assert!(sim.environment.violations.is_empty(), "{:?}", sim.environment.violations);
+        let scores = outcome.scores.clone().unwrap();
+        assert!(scores.contains(&DEFAULT_PARAMS.flags_to_win));
+        assert_eq!(scores.iter().sum::<usize>(), outcome.players.iter().map(|player| player.captures).sum::<usize>());
+        assert!(outcome.players.iter().all(|player| player.won == (scores[player.team] == DEFAULT_PARAMS.flags_to_win)));
+    }
+
+    #[test]
+    fn players_join_and_leave_mid_game() {
+        let params: TagParams = TagParams {
+            num_players: 20,
+            num_it: 2,
+            agent_type: AgentType::BasicDirectional,
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+        sim.run_headless(Option::from(20));
+        let mut its: Vec<usize> = sim.environment.it.iter().copied().collect();
+        its.sort_unstable();
+        for it in &its {
+            assert!(sim.leave(*it));
+        }
+        let runner = (0..20).find(|id| !its.contains(id)).unwrap();
+        assert!(sim.leave(runner));
+        assert!(!sim.leave(99));
+        assert_eq!(2, sim.environment.it.len());
+        assert!(its.iter().all(|it| !sim.environment.it.contains(it)));
+
+        assert_eq!(20, sim.join());
+        assert_eq!(21, sim.join());
+        assert!(sim.leave(21));
+        assert_eq!(22, sim.join());
+        sim.run_headless(Option::from(100));
+        assert!(sim.environment.violations.is_empty(), "{:?}", sim.environment.violations);
+        assert_eq!(2, sim.environment.it.len());
+        for agent in sim.environment.agents.iter() {
+            assert_eq!(sim.environment.it.contains(agent.key()), agent.player.is_it);
+        }
+    }
+
+    #[test]
+    fn continuous_games_follow_the_rules() {
+        let params: TagParams = TagParams {
+            num_players: 100,
+            num_it: 5,
+            agent_type: AgentType::BasicDirectional,
+            time_mode: TimeMode::Continuous,
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+        let reports: Vec<StepReport> = (0..200).map(|_| sim.step()).collect();
+        assert!(sim.environment.violations.is_empty(), "{:?}", sim.environment.violations);
+        assert!(reports.iter().any(|report| !report.tags.is_empty()));
+        assert!(reports.iter().all(|report| report.elapsed == 1.));
+        // nobody is caught twice in one step
+        for report in &reports {
+            let targets: std::collections::HashSet<usize> = report.tags.iter().map(|(_, target)| *target).collect();
+            assert_eq!(report.tags.len(), targets.len());
+        }
+        assert!((sim.environment.time - reports.iter().map(|report| report.elapsed as f64).sum::<f64>()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn schedules_take_the_same_steps() {
+        for schedule in [Schedule::Simultaneous, Schedule::RandomOrder, Schedule::RandomActivation, Schedule::Alternating] {
+            let params: TagParams = TagParams {
+                num_players: 50,
+                num_it: 3,
+                agent_type: AgentType::BasicDirectional,
+                schedule,
+                ..DEFAULT_PARAMS
+            };
+            let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+            let reports: Vec<StepReport> = (0..100).map(|_| sim.step()).collect();
+            assert_eq!(100, sim.environment.steps, "{:?}", schedule);
+            assert!(reports.iter().any(|report| !report.tags.is_empty()), "{:?}", schedule);
+            assert_eq!(3, sim.environment.it.len(), "{:?}", schedule);
+            // only a shuffled order has agents acting on a state that's moved on since they chose
+            if schedule != Schedule::RandomOrder {
+                assert!(sim.environment.violations.is_empty(), "{:?}: {:?}", schedule, sim.environment.violations);
+            }
+        }
+    }
+
+    #[test]
+    fn asynchronous_agents_act_when_ready() {
+        let params: TagParams = TagParams {
+            num_players: 30,
+            num_it: 2,
+            agent_type: AgentType::BasicDirectional,
+            time_mode: TimeMode::Asynchronous,
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+        let start: Vec<Point> = (0..30).map(|agent| sim.environment.agents.get(&agent).unwrap().player.position).collect();
+        sim.run_headless(Option::from(100));
+        let moved = (0..30).filter(|agent| sim.environment.agents.get(agent).unwrap().player.position != start[*agent]).count();
+        assert!(moved > 0);
+        assert_eq!(100, sim.environment.steps);
+        assert_eq!(30, sim.latencies.len());
+
+        assert!(sim.leave(3));
+        sim.join();
+        sim.run_headless(Option::from(10));
+        assert_eq!(30, sim.environment.agents.len());
+        sim.reset();
+        assert!(sim.runtime.is_none());
+    }
+
+    /// The default strategy, except that player 0 thinks for far too long.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Sluggish(Player);
+
+    impl Agent for Sluggish {
+        fn act(&self, env: &TagEnvironment<Self>, rng: &mut StdRng) -> Action {
+            if self.0.id == 0 {
+                thread::sleep(Duration::from_millis(200));
+            }
+            self.0.choose(env, rng)
+        }
+        fn create(id: usize, params: &TagParams, rng: &mut StdRng) -> Self { Sluggish(Player::create(id, params, rng)) }
+        fn update(&mut self, position: &Point) { self.0.update(position) }
+        fn update_motion(&mut self, velocity: Vector, heading: f32) { self.0.update_motion(velocity, heading) }
+        fn update_stamina(&mut self, stamina: f32) { self.0.update_stamina(stamina) }
+        fn update_attributes(&mut self, speed: f32, reach: f32) { self.0.update_attributes(speed, reach) }
+        fn update_team(&mut self, team: usize) { self.0.update_team(team) }
+        fn player(&self) -> Player { self.0 }
+        fn tag(&mut self, by: usize) { self.0.tag(by) }
+        fn untag(&mut self) { self.0.untag() }
+    }
+
+    #[test]
+    fn slow_agents_run_out_of_time() {
+        let params: TagParams = TagParams {
+            num_players: 10,
+            threads: 2,
+            budget: 50.,
+            fallback: Fallback::Repeat,
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<Sluggish> = Simulation::new(params);
+        let start = sim.environment.agents.get(&0).unwrap().0.position;
+        let began = Instant::now();
+        sim.run_headless(Option::from(3));
+        assert!(began.elapsed() < Duration::from_millis(600));
+        assert_eq!(start, sim.environment.agents.get(&0).unwrap().0.position);
+        let slow = sim.latencies.get(&0).unwrap();
+        assert_eq!((3, 3), (slow.decisions, slow.overruns));
+        for agent in 1..10 {
+            let latency = sim.latencies.get(&agent).unwrap();
+            assert_eq!((3, 0), (latency.decisions, latency.overruns));
+        }
+    }
+
+    #[test]
+    fn repeated_moves_keep_going() {
+        let params: TagParams = TagParams {
+            num_players: 1,
+            fallback: Fallback::Repeat,
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<Player> = Simulation::new(params);
+        let position = sim.environment.agents.get(&0).unwrap().position;
+        let latency = Duration::from_millis(1);
+        sim.settle(vec![(0, Some(Action::Move(Point::new(position.x + 1., position.y - 2.))), latency)]);
+        assert_eq!(vec![(0, Action::Steer(Vector::new(1., -2.)))], sim.settle(vec![(0, None, latency)]));
+    }
+
+    #[test]
+    fn asynchronous_actions_come_in_as_they_are_decided() {
+        let params: TagParams = TagParams {
+            num_players: 4,
+            time_mode: TimeMode::Asynchronous,
+            ..DEFAULT_PARAMS
+        };
+        let sim: Simulation<Sluggish> = Simulation::new(params);
+        let mut runtime: Runtime<Sluggish> = Runtime::new(0);
+        runtime.publish(&sim.environment);
+        let mut arrivals: Vec<(usize, Duration)> = Vec::new();
+        while arrivals.len() < 4 {
+            arrivals.extend(runtime.collect().into_iter().map(|(agent, _, latency)| (agent, latency)));
+        }
+        // the slow agent comes last, after the others it would come before in order of id
+        let (last, latency) = arrivals[3];
+        assert_eq!(0, last);
+        assert!(latency >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn slow_asynchronous_agents_act_less_often() {
+        let params: TagParams = TagParams {
+            num_players: 4,
+            time_mode: TimeMode::Asynchronous,
+            ..DEFAULT_PARAMS
+        };
+        let mut sim: Simulation<Sluggish> = Simulation::new(params);
+        sim.run_headless(Option::from(30));
+        let decisions = |agent: usize| sim.latencies.get(&agent).map_or(0, |latency| latency.decisions);
+        for agent in 1..4 {
+            assert!(decisions(0) < decisions(agent));
+        }
+    }
+
+    #[test]
+    fn bodies_do_not_overlap() {
+        // blocking never lets bodies overlap, while pushing only settles a crowd so far each step
+        for (collisions, closest) in &[(Collisions::Block, 7.9), (Collisions::Push, 6.0)] {
+            let params: TagParams = TagParams {
+                num_players: 100,
+                num_it: 5,
+                agent_type: AgentType::BasicDirectional,
+                body_radius: 4.0,
+                collisions: *collisions,
+                ..DEFAULT_PARAMS
+            };
+            let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+            sim.run_headless(Option::from(200));
+            assert!(sim.environment.violations.is_empty(), "{:?}", sim.environment.violations);
+            let positions: Vec<Point> = sim.environment.agents.iter().map(|agent| agent.player.position).collect();
+            for (index, first) in positions.iter().enumerate() {
+                for second in &positions[index + 1..] {
+                    assert!(first.distance(*second) > *closest, "{:?}: {:?} and {:?} overlap", collisions, first, second);
+                }
+            }
+        }
+    }
+
     #[bench]
     fn bench_headless_500_directional(b: &mut Bencher) {
         let params: TagParams = TagParams {
-            speed: 5.0,
-            proximity: 2.0,
+            speed: Distribution::Fixed(5.0),
+            proximity: Distribution::Fixed(2.0),
             width: 1000,
             height: 600,
             num_players: 500,
             agent_type: AgentType::BasicDirectional,
             numbered: false,
-            num_it: 1
+            num_it: 1,
+            ..DEFAULT_PARAMS
         };
-        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params.clone());
         b.iter(|| {
             sim.step();
         });
@@ -151,16 +1102,17 @@ mod tests {
     #[bench]
     fn bench_headless_5000_directional(b: &mut Bencher) {
         let params: TagParams = TagParams {
-            speed: 5.0,
-            proximity: 2.0,
+            speed: Distribution::Fixed(5.0),
+            proximity: Distribution::Fixed(2.0),
             width: 1000,
             height: 600,
             num_players: 5000,
             agent_type: AgentType::BasicDirectional,
             numbered: false,
-            num_it: 1
+            num_it: 1,
+            ..DEFAULT_PARAMS
         };
-        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params);
+        let mut sim: Simulation<DirectionalAgent> = Simulation::new(params.clone());
         b.iter(|| {
             sim.step();
         });
@@ -169,16 +1121,17 @@ mod tests {
     #[bench]
     fn bench_headless_500_default(b: &mut Bencher) {
         let params: TagParams = TagParams {
-            speed: 5.0,
-            proximity: 2.0,
+            speed: Distribution::Fixed(5.0),
+            proximity: Distribution::Fixed(2.0),
             width: 1000,
             height: 600,
             num_players: 500,
             agent_type: AgentType::Default,
             numbered: false,
-            num_it: 1
+            num_it: 1,
+            ..DEFAULT_PARAMS
         };
-        let mut sim: Simulation<Player> = Simulation::new(params);
+        let mut sim: Simulation<Player> = Simulation::new(params.clone());
         b.iter(|| {
             sim.step();
         });
@@ -188,16 +1141,17 @@ mod tests {
     #[bench]
     fn bench_headless_5000_default(b: &mut Bencher) {
         let params: TagParams = TagParams {
-            speed: 5.0,
-            proximity: 2.0,
+            speed: Distribution::Fixed(5.0),
+            proximity: Distribution::Fixed(2.0),
             width: 1000,
             height: 600,
             num_players: 5000,
             agent_type: AgentType::Default,
             numbered: false,
-            num_it: 1
+            num_it: 1,
+            ..DEFAULT_PARAMS
         };
-        let mut sim: Simulation<Player> = Simulation::new(params);
+        let mut sim: Simulation<Player> = Simulation::new(params.clone());
         b.iter(|| {
             sim.step();
         });
@@ -206,16 +1160,17 @@ mod tests {
     #[bench]
     fn bench_headless_50000_default(b: &mut Bencher) {
         let params: TagParams = TagParams {
-            speed: 5.0,
-            proximity: 2.0,
+            speed: Distribution::Fixed(5.0),
+            proximity: Distribution::Fixed(2.0),
             width: 1000,
             height: 600,
             num_players: 50000,
             agent_type: AgentType::Default,
             numbered: false,
-            num_it: 1
+            num_it: 1,
+            ..DEFAULT_PARAMS
         };
-        let mut sim: Simulation<Player> = Simulation::new(params);
+        let mut sim: Simulation<Player> = Simulation::new(params.clone());
         b.iter(|| {
             sim.step();
         });
@@ -0,0 +1,225 @@
+use iced::Point;
+use std::collections::HashMap;
+use crate::arena::Arena;
+
+/// A uniform grid of buckets over the field, used to answer neighbourhood queries
+/// without scanning every agent.
+/// Buckets hold the id and position of each agent, so distances can be checked without
+/// going back to the environment.
+/// On a wrapping field, queries near one edge also search near the opposite edge.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex {
+    arena: Arena,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<(usize, Point)>>,
+    locations: HashMap<usize, usize>,
+}
+
+impl SpatialIndex {
+
+    /// Creates an empty index covering the field with square buckets of side `cell_size`.
+    pub fn new(arena: Arena, cell_size: f32) -> Self {
+        let cell_size = f32::max(cell_size, 1.0);
+        let columns = usize::max(1, (arena.width / cell_size).ceil() as usize);
+        let rows = usize::max(1, (arena.height / cell_size).ceil() as usize);
+        SpatialIndex {
+            arena,
+            cell_size,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+            locations: HashMap::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+        self.locations.clear();
+    }
+
+    /// Adds an agent at the given position, moving it if it is already indexed.
+    pub fn insert(&mut self, id: usize, position: Point) {
+        self.remove(id);
+        let cell = self.cell_index(position);
+        self.cells[cell].push((id, position));
+        self.locations.insert(id, cell);
+    }
+
+    pub fn remove(&mut self, id: usize) -> bool {
+        match self.locations.remove(&id) {
+            None => false,
+            Some(cell) => {
+                self.cells[cell].retain(|(other, _)| *other != id);
+                true
+            }
+        }
+    }
+
+    /// Moves an indexed agent, only touching the buckets if it has changed bucket.
+    pub fn update(&mut self, id: usize, position: Point) {
+        let cell = self.cell_index(position);
+        match self.locations.get(&id) {
+            Some(old) if *old == cell => {
+                for entry in self.cells[cell].iter_mut().filter(|(other, _)| *other == id) {
+                    entry.1 = position;
+                }
+            }
+            _ => self.insert(id, position),
+        }
+    }
+
+    /// The ids of all agents within `radius` of `point`, in ascending order.
+    pub fn within(&self, point: Point, radius: f32) -> Vec<usize> {
+        let mut found: Vec<usize> = Vec::new();
+        for image in self.arena.images(point, radius) {
+            self.within_image(image, radius, &mut found);
+        }
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    fn within_image(&self, point: Point, radius: f32, found: &mut Vec<usize>) {
+        let (min_column, min_row) = self.cell_coordinates(Point::new(point.x - radius, point.y - radius));
+        let (max_column, max_row) = self.cell_coordinates(Point::new(point.x + radius, point.y + radius));
+        for row in min_row..=max_row {
+            for column in min_column..=max_column {
+                found.extend(self.cells[row * self.columns + column].iter()
+                    .filter(|(_, position)| position.distance(point) <= radius)
+                    .map(|(id, _)| *id));
+            }
+        }
+    }
+
+    /// The nearest agent to `point` accepted by `predicate`, and its distance.
+    /// Buckets are searched in rings moving outwards from the point, stopping once no closer agent is possible.
+    /// Ties are broken by the lower id.
+    pub fn nearest<F>(&self, point: Point, mut predicate: F) -> Option<(usize, f32)>
+        where
+            F: FnMut(usize) -> bool
+    {
+        let mut best: Option<(usize, f32)> = None;
+        for image in self.arena.images(point, f32::INFINITY) {
+            match best {
+                Some((_, best_distance)) if self.arena.distance_to_field(image) > best_distance => {}
+                _ => best = self.nearest_image(image, best, &mut predicate),
+            }
+        }
+        best
+    }
+
+    /// Carries on a search for the nearest agent from one image of the query point.
+    fn nearest_image<F>(&self, point: Point, mut best: Option<(usize, f32)>, predicate: &mut F) -> Option<(usize, f32)>
+        where
+            F: FnMut(usize) -> bool
+    {
+        let (column, row) = self.cell_coordinates(point);
+        let max_ring = usize::max(self.columns, self.rows);
+        for ring in 0..=max_ring {
+            let min_row = row.saturating_sub(ring);
+            let max_row = usize::min(self.rows - 1, row + ring);
+            let min_column = column.saturating_sub(ring);
+            let max_column = usize::min(self.columns - 1, column + ring);
+            for cell_row in min_row..=max_row {
+                for cell_column in min_column..=max_column {
+                    let on_ring = cell_row + ring == row || cell_row == row + ring
+                        || cell_column + ring == column || cell_column == column + ring;
+                    if !on_ring {
+                        continue;
+                    }
+                    for (id, position) in &self.cells[cell_row * self.columns + cell_column] {
+                        let distance = position.distance(point);
+                        let closer = match best {
+                            None => true,
+                            Some((best_id, best_distance)) => distance < best_distance || (distance == best_distance && *id < best_id),
+                        };
+                        if closer && predicate(*id) {
+                            best = Some((*id, distance));
+                        }
+                    }
+                }
+            }
+            if let Some((_, best_distance)) = best {
+                if best_distance <= ring as f32 * self.cell_size {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
+    fn cell_coordinates(&self, point: Point) -> (usize, usize) {
+        let column = f32::max(0.0, point.x / self.cell_size) as usize;
+        let row = f32::max(0.0, point.y / self.cell_size) as usize;
+        (usize::min(column, self.columns - 1), usize::min(row, self.rows - 1))
+    }
+
+    fn cell_index(&self, point: Point) -> usize {
+        let (column, row) = self.cell_coordinates(point);
+        row * self.columns + column
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::spatial_index::SpatialIndex;
+    use crate::arena::{Arena, Boundary};
+    use iced::Point;
+
+    fn base_index() -> SpatialIndex {
+        let mut index = SpatialIndex::new(Arena::new(100., 100., Boundary::Clamp), 10.);
+        index.insert(0, Point::new(5., 5.));
+        index.insert(1, Point::new(12., 5.));
+        index.insert(2, Point::new(50., 50.));
+        index.insert(3, Point::new(99., 99.));
+        index
+    }
+
+    #[test]
+    fn finds_neighbours() {
+        let index = base_index();
+        assert_eq!(vec![0, 1], index.within(Point::new(8., 5.), 5.));
+        assert_eq!(vec![0], index.within(Point::new(0., 0.), 10.));
+        assert_eq!(vec![0, 1, 2, 3], index.within(Point::new(50., 50.), 100.));
+        assert!(index.within(Point::new(30., 80.), 5.).is_empty());
+    }
+
+    #[test]
+    fn finds_nearest() {
+        let index = base_index();
+        assert_eq!(Some((2, 0.)), index.nearest(Point::new(50., 50.), |_| true));
+        assert_eq!(Some(1), index.nearest(Point::new(50., 50.), |id| id != 2).map(|(id, _)| id));
+        assert_eq!(Some(0), index.nearest(Point::new(8.5, 5.), |_| true).map(|(id, _)| id));
+        assert_eq!(None, index.nearest(Point::new(8.5, 5.), |_| false));
+    }
+
+    #[test]
+    fn tracks_moves_and_removals() {
+        let mut index = base_index();
+        index.update(2, Point::new(6., 6.));
+        assert_eq!(vec![0, 2], index.within(Point::new(5., 5.), 2.));
+        index.update(2, Point::new(7., 7.));
+        assert_eq!(vec![0, 2], index.within(Point::new(5., 5.), 3.));
+        assert!(index.remove(0));
+        assert!(!index.remove(0));
+        assert_eq!(vec![2], index.within(Point::new(5., 5.), 3.));
+        assert_eq!(vec![1, 2, 3], index.within(Point::new(50., 50.), 100.));
+    }
+
+    #[test]
+    fn searches_across_wrapped_edges() {
+        let mut index = SpatialIndex::new(Arena::new(100., 100., Boundary::Wrap), 10.);
+        index.insert(0, Point::new(1., 50.));
+        index.insert(1, Point::new(90., 50.));
+        index.insert(2, Point::new(60., 50.));
+        assert_eq!(vec![0], index.within(Point::new(99., 50.), 3.));
+        assert_eq!(Some(0), index.nearest(Point::new(98., 50.), |_| true).map(|(id, _)| id));
+        assert_eq!(Some(1), index.nearest(Point::new(98., 50.), |id| id != 0).map(|(id, _)| id));
+    }
+
+}
@@ -1,17 +1,28 @@
-use crate::action::Action;
+use crate::action::{Action, Rejection};
 use crate::environment::Environment;
 use std::borrow::Borrow;
-use iced::{canvas, Point, Color, HorizontalAlignment, VerticalAlignment};
+use iced::{canvas, Point, Color, HorizontalAlignment, VerticalAlignment, Vector};
 use iced::canvas::{Path, Frame};
-use rand::{thread_rng, Rng};
+use rand::Rng;
+use rand::rngs::StdRng;
 use crate::parameters::TagParams;
-use crate::agents::agent::Agent;
+use crate::agents::agent::{Agent, Player};
 use iced::widget::canvas::Layer;
 use async_std::sync::Arc;
 use iced_native::Size;
 use iced_wgpu::Primitive;
 use dashmap::DashMap;
-use std::collections::HashSet;
+use std::collections::{HashSet, HashMap, BTreeMap};
+use crate::resolution::{ConflictPolicy, StepReport, Conflict};
+use crate::spatial_index::SpatialIndex;
+use crate::rules::{TagRules, HitTest};
+use crate::arena::{Arena, Boundary};
+use crate::obstacles::Obstacle;
+use crate::collisions::Collisions;
+use crate::motion::{Motion, length};
+use crate::continuous::contact_time;
+use crate::game_mode::{GameMode, Capture, TEAMS, home_side};
+use crate::flag::Flag;
 
 /// The state of the environment of the simulation.
 #[derive(Debug, Clone)]
@@ -19,32 +30,116 @@ pub struct TagEnvironment<P>
 where
     P: Agent
 {
-    // Just having a map like this really isn't ideal;
-    //  this should be broken into a grid of buckets,
-    //  or something like an R* tree.
     pub(crate) agents: DashMap<usize, P>,
-    pub(crate) width: f32,
-    pub(crate) height: f32,
+    /// The field, and what happens at its edges.
+    pub(crate) arena: Arena,
+    /// Obstacles on the field, which block both movement and tags.
+    pub(crate) obstacles: Vec<Obstacle>,
     pub(crate) it: HashSet<usize>,
     pub(crate) show_numbers: bool,
+    /// A grid of buckets over the agents' positions, kept up to date as agents are added and moved.
+    pub(crate) index: SpatialIndex,
+    /// The number of rejected actions from each agent.
+    pub(crate) violations: HashMap<usize, usize>,
+    /// How simultaneous tags on the same player are settled.
+    pub(crate) conflict_policy: ConflictPolicy,
+    /// The rules of tagging.
+    pub(crate) rules: TagRules,
+    /// The number of steps taken since the last reset.
+    pub(crate) steps: u64,
+    /// The step at which each player was last tagged.
+    pub(crate) tagged_at: HashMap<usize, u64>,
+    /// The step at which each player last passed 'it' on.
+    pub(crate) released_at: HashMap<usize, u64>,
+    /// The largest reach of any player, for bounding searches for players in range.
+    pub(crate) max_reach: f32,
+    /// How players' bodies are kept from overlapping.
+    pub(crate) collisions: Collisions,
+    /// The largest body radius of any player, for bounding searches for collisions.
+    pub(crate) max_radius: f32,
+    /// How players' controls turn into movement.
+    pub(crate) motion: Motion,
+    /// The stamina a player recovers in each step they move without sprinting.
+    pub(crate) recovery: f32,
+    /// One more than the highest id any agent has had since the last reset.
+    pub(crate) issued_ids: usize,
+    /// The game time since the last reset; each step of a stepped game takes one unit.
+    pub(crate) time: f64,
+    /// The game being played.
+    pub(crate) mode: GameMode,
+    /// The players frozen in place: frozen in freeze tag, or held in jail in team tag.
+    pub(crate) frozen: HashSet<usize>,
+    /// What happens to a player tagged in team tag or capture the flag.
+    pub(crate) capture: Capture,
+    /// The teams' flags in capture the flag, by team; there are none in other games.
+    pub(crate) flags: Vec<Flag>,
+    /// The number of flags each team has captured.
+    pub(crate) scores: [usize; TEAMS],
+    /// The number of flags a team has to capture to win capture the flag.
+    pub(crate) flags_to_win: usize,
 }
 
+/// How far in from the edge of their side of the field a team holds its prisoners, as a share of the field's width.
+const JAIL_INSET: f32 = 0.05;
+
+/// How far in from the edge of their side of the field a team's flag stands, as a share of the field's width.
+const FLAG_INSET: f32 = 0.15;
+
+/// The height of a flag as drawn.
+const FLAG_SIZE: f32 = 12.;
+
+/// The slack allowed on distance checks for floating point error.
+const TOLERANCE: f32 = 1e-3;
+
+/// How far short of an obstacle a player running into it stops.
+const CLEARANCE: f32 = 1e-2;
+
+/// The angles, in degrees, that players try turning through in turn to get around an obstacle.
+const DETOURS: [f32; 11] = [30., -30., 60., -60., 90., -90., 120., -120., 150., -150., 180.];
+
+/// The most times overlapping players are pushed apart in one step.
+const PUSH_PASSES: usize = 32;
+
 impl<P> Environment<Action, P> for TagEnvironment<P>
     where
         P: Agent
 {
 
-    fn reset(&mut self, params: TagParams) {
+    type Rejection = Rejection;
+    type Report = StepReport;
+
+    fn reset(&mut self, params: &TagParams, rng: &mut StdRng) {
         self.show_numbers = params.numbered;
+        self.conflict_policy = params.conflict_policy;
+        self.rules = params.rules;
+        self.obstacles = params.obstacles.clone();
+        self.collisions = params.collisions;
+        self.motion = params.motion;
+        self.recovery = params.recovery as f32;
+        self.mode = params.mode;
+        self.capture = params.capture;
+        self.flags_to_win = params.flags_to_win;
+        self.scores = [0; TEAMS];
+        self.flags = self.plant_flags();
+        self.frozen.clear();
         self.agents.clear();
-        let mut rng = thread_rng();
+        self.index.clear();
+        self.violations.clear();
+        self.steps = 0;
+        self.tagged_at.clear();
+        self.released_at.clear();
+        self.max_reach = 0.;
+        self.max_radius = 0.;
+        self.issued_ids = 0;
+        self.time = 0.;
 
         for agent in 0..params.num_players {
-            self.add_agent(P::create(agent, params))
+            self.add_agent(P::create(agent, params, rng))
         };
 
         self.it.clear();
-        let mut to_pick = params.num_it;
+        // in team games, everyone both chases and runs
+        let mut to_pick = if params.mode.has_teams() { 0 } else { params.num_it };
         while to_pick > 0 {
             let it: usize = rng.gen_range(0, params.num_players);
             if self.it.insert(it) {
@@ -54,42 +149,204 @@ impl<P> Environment<Action, P> for TagEnvironment<P>
             }
         }
 
+        // the starting 'it's draw their attributes again from their own distributions, unless they're on the roster
+        if params.it_speed.is_some() || params.it_proximity.is_some() {
+            let mut its: Vec<usize> = self.it.iter().copied().filter(|it| !params.roster.contains_key(it)).collect();
+            its.sort_unstable();
+            for it in its {
+                let player = self.agents.get(&it).unwrap().player();
+                let speed = params.it_speed.as_ref().map_or(player.speed, |speed| speed.sample(it, rng) as f32);
+                let reach = params.it_proximity.as_ref().map_or(player.reach, |reach| reach.sample(it, rng) as f32);
+                self.agents.get_mut(&it).unwrap().update_attributes(speed, reach);
+                self.max_reach = f32::max(self.max_reach, reach);
+            }
+        }
+
     }
 
+    /// Adds an agent, or updates it if it's already present; an agent that is 'it' joins the 'it's.
     fn add_agent(&mut self, agent: P) {
+        self.index.insert(agent.player().id, agent.player().position);
+        if agent.player().is_it {
+            self.it.insert(agent.player().id);
+        } else {
+            self.it.remove(&agent.player().id);
+        }
+        self.max_reach = f32::max(self.max_reach, agent.player().reach);
+        self.max_radius = f32::max(self.max_radius, agent.player().radius);
+        self.issued_ids = usize::max(self.issued_ids, agent.player().id + 1);
         match &self.agents.borrow().insert(agent.player().id, agent) {
             None => {
-                log::debug!("Agent {:?} added to the environment.", &agent);
+                log::debug!("Agent {:?} added to the environment.", agent);
             }
             Some(agent) => {
-                log::warn!("Agent {:?} already present in the environment. Agent was updated instead.", &agent);
+                log::warn!("Agent {:?} already present in the environment. Agent was updated instead.", agent);
             }
         };
     }
 
-    fn step(&mut self, agent: usize, action: &Action) {
+    /// Takes an agent out of the game, dropping any flag they're carrying where they are. If they were 'it',
+    /// the nearest player who isn't takes over from them, except in infection tag while anyone else is still infected.
+    fn remove_agent(&mut self, agent: usize) -> Option<P> {
+        self.drop_flag(agent);
+        let (_, removed) = self.agents.remove(&agent)?;
+        self.index.remove(agent);
+        self.tagged_at.remove(&agent);
+        self.released_at.remove(&agent);
+        self.frozen.remove(&agent);
+        log::info!("Agent {:?} has left the game.", agent);
+        if self.it.remove(&agent) && (self.mode != GameMode::Infection || self.it.is_empty()) {
+            if let Some((next, _)) = self.index.nearest(removed.player().position, |id| !self.it.contains(&id) && !self.frozen.contains(&id)) {
+                self.agents.get_mut(&next).unwrap().tag(agent);
+                self.it.insert(next);
+                self.tagged_at.insert(next, self.steps);
+                log::info!("Agent {:?} is 'it' in place of {:?}.", next, agent);
+            }
+        }
+        Some(removed)
+    }
+
+    /// Applies an action if it's within the rules, otherwise counts a violation against the agent.
+    /// A tag that misses still counts as applied, and a move-and-tag that misses still moves.
+    fn step(&mut self, agent: usize, action: &Action, rng: &mut StdRng) -> Result<(), Rejection> {
         log::debug!("Applying action {:?} from agent {:?}", action, agent);
+        if let Err(rejection) = self.validate(agent, action) {
+            self.reject(agent, action, rejection);
+            return Err(rejection);
+        }
         match action {
-            Action::Tag(other) => {
-                if self.it.insert(*other) {
-                    self.agents.get_mut(&agent).unwrap().untag();
-                    self.agents.get_mut(&other).unwrap().tag(agent);
-                    self.it.remove(&agent);
-                    log::info!("Agent {:?} has tagged agent {:?}.", agent, other)
-                } else {
-                    log::info!("{:?} has already been tagged.", *other);
+            Action::Tag(target) if self.missed(agent, self.agents.get(&agent).unwrap().player().position, *target, rng) => {}
+            Action::MoveAndTag(position, target) if self.missed(agent, self.destination(self.agents.get(&agent).unwrap().player(), *position).unwrap(), *target, rng) => {
+                self.apply(agent, &Action::Move(*position));
+            }
+            _ => self.apply(agent, action),
+        }
+        if self.collisions == Collisions::Push {
+            self.push_apart();
+        }
+        Ok(())
+    }
+
+    /// Applies every agent's action as if they all happened at once.
+    /// All actions are checked against the state at the start of the step, then competing tags on the same
+    /// player are settled by the conflict policy, and finally players who weren't caught make their moves.
+    /// A move-and-tag is checked as a whole, with the tag made from where the tagger moves to.
+    /// Each tag may miss, depending on the rules; only the tags that land go on to be settled.
+    /// Flags are dropped and then picked up after the tags, by those who weren't caught, and players going for the same
+    /// flag are settled by the conflict policy like competing tags.
+    /// Moves are made according to the motion model; a move-and-tag's tag is made from wherever the move ends up.
+    /// Moves into obstacles stop just short of them, and players' bodies are then kept apart by the collision policy;
+    /// when blocking, moves are made in the order given.
+    /// Players who end up out of bounds under the eliminate policy leave the game, and are reported.
+    fn step_all(&mut self, actions: Vec<(usize, Action)>, rng: &mut StdRng) -> StepReport {
+        let mut report = StepReport::default();
+        self.resolve(actions, rng, &mut report);
+        self.end_step(&mut report);
+        report
+    }
+
+    /// Each action is checked against the game as the actions before it left it, so an agent acting on an
+    /// out-of-date view of the game may find their action refused.
+    fn step_in_order(&mut self, actions: Vec<(usize, Action)>, rng: &mut StdRng) -> StepReport {
+        let mut report = StepReport::default();
+        for (agent, action) in actions {
+            self.activate(agent, action, rng, &mut report);
+        }
+        self.end_step(&mut report);
+        report
+    }
+
+    /// Every player moves at the velocity the motion model gives their action (a tag on its own stands still)
+    /// for the whole of `horizon`. Whenever an 'it' comes within reach of someone they can tag, the target is tagged
+    /// right then and stops where they are, and play carries on from there to the end of the horizon; nobody is tagged twice in one call.
+    /// Contact is predicted from straight-line motion, and obstacles and the boundary are applied at each contact;
+    /// tags from contact are certain, so the rules' miss chances don't apply.
+    /// In freeze tag, rescues are made once everyone has moved, by those still within reach of the teammate they tagged,
+    /// and in capture the flag, flags are dropped and picked up then too, by those still able to, who stand still till then.
+    /// Each call counts as a step for the rules' cooldowns.
+    fn advance(&mut self, actions: Vec<(usize, Action)>, horizon: f32, rng: &mut StdRng) -> StepReport {
+        let mut report = StepReport::default();
+        let mut controls: Vec<(usize, Vector, f32, bool)> = Vec::with_capacity(actions.len());
+        let mut rescues: Vec<(usize, usize)> = Vec::new();
+        let mut pick_ups: BTreeMap<usize, Vec<(usize, Point)>> = BTreeMap::new();
+        let mut drops: Vec<usize> = Vec::new();
+        for (agent, action) in actions {
+            if let Err(rejection) = self.validate(agent, &action) {
+                self.reject(agent, &action, rejection);
+                report.rejections.push((agent, rejection));
+                continue;
+            }
+            if let Action::Tag(target) | Action::MoveAndTag(_, target) = action {
+                if self.frozen.contains(&target) {
+                    rescues.push((agent, target));
                 }
             }
-            Action::Move(position) => {
-                self.agents.get_mut(&agent).unwrap().update(position);
+            match action {
+                Action::PickUp(team) => pick_ups.entry(team).or_default().push((agent, self.agents.get(&agent).unwrap().player().position)),
+                Action::Drop => drops.push(agent),
+                _ => {}
             }
+            let (velocity, sprinting) = match action {
+                Action::Tag(_) | Action::PickUp(_) | Action::Drop => (Vector::new(0., 0.), false),
+                Action::Move(position) | Action::MoveAndTag(position, _) => (self.toward(agent, position), false),
+                Action::Steer(velocity) => (velocity, false),
+                Action::Sprint(velocity) => (velocity, true),
+            };
+            let (velocity, heading) = self.control(self.agents.get(&agent).unwrap().player(), velocity, sprinting);
+            controls.push((agent, velocity, heading, sprinting));
         }
-    }
 
-    fn step_all(&mut self, actions: Vec<Action>) {
-        for (index, act) in actions.iter().enumerate() {
-            self.step(index, act)
+        let mut caught: HashMap<usize, usize> = HashMap::new();
+        let mut targeted: HashSet<usize> = HashSet::new();
+        let mut remaining = horizon;
+        while remaining > 0. {
+            let velocities: HashMap<usize, Vector> = controls.iter().map(|(agent, velocity, _, _)| (*agent, *velocity)).collect();
+            let contact = self.next_contact(&velocities, remaining, &targeted);
+            let elapsed = contact.map_or(remaining, |(time, _, _)| time);
+            if elapsed > 0. {
+                let mut eliminated: Vec<usize> = Vec::new();
+                for (agent, velocity, heading, sprinting) in &controls {
+                    if !self.travel(*agent, *velocity, *heading, *sprinting, elapsed) {
+                        eliminated.push(*agent);
+                    }
+                }
+                controls.retain(|(agent, _, _, _)| !eliminated.contains(agent));
+                report.eliminated.extend(eliminated);
+                if self.collisions == Collisions::Push {
+                    self.push_apart();
+                }
+            }
+            remaining -= elapsed;
+            let (tagger, target) = match contact {
+                None => break,
+                Some((_, tagger, target)) => (tagger, target),
+            };
+            // obstacles and the boundary can still keep them apart
+            if self.agents.contains_key(&tagger) && self.agents.contains_key(&target) && self.validate(tagger, &Action::Tag(target)).is_ok() {
+                self.apply(tagger, &Action::Tag(target));
+                report.tags.push((tagger, target));
+                caught.insert(target, tagger);
+                // whoever is caught stops where they are for the rest of the step, if they're still in the game
+                controls.retain(|(agent, _, _, _)| self.agents.contains_key(agent));
+                if let Some(control) = controls.iter_mut().find(|(agent, _, _, _)| *agent == target) {
+                    control.1 = Vector::new(0., 0.);
+                    control.3 = false;
+                }
+            }
+            targeted.insert(target);
+        }
+        for (rescuer, target) in rescues {
+            if self.frozen.contains(&target) && self.validate(rescuer, &Action::Tag(target)).is_ok() {
+                self.apply(rescuer, &Action::Tag(target));
+                report.rescues.push((rescuer, target));
+            }
         }
+        self.handle_flags(pick_ups, drops, &caught, rng, &mut report);
+        self.score(&mut report);
+        self.steps += 1;
+        self.time += horizon as f64;
+        report.elapsed = horizon;
+        report
     }
 
 }
@@ -111,11 +368,946 @@ impl<P> TagEnvironment<P>
         P: Agent
 {
 
+    /// Creates an empty environment for the given parameters; `reset` populates it.
+    pub fn new(params: &TagParams) -> Self {
+        let arena = Arena::new(params.width as f32, params.height as f32, params.boundary);
+        let mut env = TagEnvironment {
+            agents: DashMap::with_capacity(params.num_players),
+            arena,
+            obstacles: params.obstacles.clone(),
+            it: HashSet::new(),
+            show_numbers: params.numbered,
+            violations: HashMap::new(),
+            conflict_policy: params.conflict_policy,
+            rules: params.rules,
+            steps: 0,
+            tagged_at: HashMap::new(),
+            released_at: HashMap::new(),
+            max_reach: 0.,
+            collisions: params.collisions,
+            max_radius: 0.,
+            motion: params.motion,
+            recovery: params.recovery as f32,
+            issued_ids: 0,
+            time: 0.,
+            mode: params.mode,
+            frozen: HashSet::new(),
+            capture: params.capture,
+            flags: Vec::new(),
+            scores: [0; TEAMS],
+            flags_to_win: params.flags_to_win,
+            index: SpatialIndex::new(arena, f64::max(params.proximity.mean(), params.speed.mean()) as f32),
+        };
+        env.flags = env.plant_flags();
+        env
+    }
+
+    /// The teams' flags on their stands, for capture the flag.
+    fn plant_flags(&self) -> Vec<Flag> {
+        if self.mode != GameMode::Flag {
+            return Vec::new();
+        }
+        (0..TEAMS).map(|team| Flag::new(team, self.flag_stand(team))).collect()
+    }
+
+    /// Applies an action that has already been validated.
+    fn apply(&mut self, agent: usize, action: &Action) {
+        match action {
+            Action::Tag(other) if self.frozen.contains(other) => {
+                self.frozen.remove(other);
+                log::info!("Agent {:?} has unfrozen agent {:?}.", agent, other)
+            }
+            Action::Tag(other) if self.mode == GameMode::Freeze => {
+                let heading = self.agents.get(other).unwrap().player().heading;
+                self.agents.get_mut(other).unwrap().update_motion(Vector::new(0., 0.), heading);
+                self.frozen.insert(*other);
+                self.tagged_at.insert(*other, self.steps);
+                log::info!("Agent {:?} has frozen agent {:?}.", agent, other)
+            }
+            Action::Tag(other) if self.mode == GameMode::Elimination => {
+                log::info!("Agent {:?} has tagged agent {:?} out of the game.", agent, other);
+                self.remove_agent(*other);
+            }
+            Action::Tag(other) if self.mode.has_teams() => {
+                let team = self.agents.get(&agent).unwrap().player().team;
+                self.drop_flag(*other);
+                match self.capture {
+                    Capture::Jail => {
+                        let jail = self.jail(team);
+                        let heading = self.agents.get(other).unwrap().player().heading;
+                        self.agents.get_mut(other).unwrap().update(&jail);
+                        self.agents.get_mut(other).unwrap().update_motion(Vector::new(0., 0.), heading);
+                        self.index.update(*other, jail);
+                        self.frozen.insert(*other);
+                        log::info!("Agent {:?} has sent agent {:?} to jail.", agent, other)
+                    }
+                    Capture::Switch => {
+                        self.agents.get_mut(other).unwrap().update_team(team);
+                        log::info!("Agent {:?} has won agent {:?} over to team {:?}.", agent, other, team)
+                    }
+                }
+                self.tagged_at.insert(*other, self.steps);
+            }
+            Action::Tag(other) if self.mode == GameMode::Infection => {
+                self.agents.get_mut(other).unwrap().tag(agent);
+                self.it.insert(*other);
+                self.tagged_at.insert(*other, self.steps);
+                log::info!("Agent {:?} has infected agent {:?}.", agent, other)
+            }
+            Action::Tag(other) => {
+                self.agents.get_mut(&agent).unwrap().untag();
+                self.agents.get_mut(other).unwrap().tag(agent);
+                self.it.remove(&agent);
+                self.it.insert(*other);
+                self.released_at.insert(agent, self.steps);
+                self.tagged_at.insert(*other, self.steps);
+                log::info!("Agent {:?} has tagged agent {:?}.", agent, other)
+            }
+            Action::Move(position) => {
+                self.drive(agent, self.toward(agent, *position), false);
+            }
+            Action::Steer(velocity) => {
+                self.drive(agent, *velocity, false);
+            }
+            Action::Sprint(velocity) => {
+                self.drive(agent, *velocity, true);
+            }
+            Action::MoveAndTag(position, other) => {
+                self.apply(agent, &Action::Move(*position));
+                self.apply(agent, &Action::Tag(*other));
+            }
+            Action::PickUp(team) => {
+                self.flags[*team].carrier = Some(agent);
+                log::info!("Agent {:?} has picked up team {:?}'s flag.", agent, team)
+            }
+            Action::Drop => self.drop_flag(agent),
+        }
+    }
+
+    /// Puts down the flag `agent` is carrying, if any, where they stand.
+    fn drop_flag(&mut self, agent: usize) {
+        let position = match self.agents.get(&agent) {
+            None => return,
+            Some(agent) => agent.player().position,
+        };
+        if let Some(flag) = self.flags.iter_mut().find(|flag| flag.carrier == Some(agent)) {
+            flag.drop_at(position);
+            log::info!("Agent {:?} has dropped team {:?}'s flag.", agent, flag.team)
+        }
+    }
+
+    /// The velocity that would take an agent straight to `position` in one step.
+    fn toward(&self, agent: usize, position: Point) -> Vector {
+        let [delta_x, delta_y] = self.arena.delta(self.agents.get(&agent).unwrap().player().position, position);
+        Vector::new(delta_x, delta_y)
+    }
+
+    /// Moves an agent asking to move at `velocity` according to the motion model, as far as obstacles
+    /// (and, when blocking, other players) allow, to wherever the boundary policy then puts them.
+    /// A sprinting player may go faster than their speed for as long as their stamina lasts, using up the distance
+    /// gone beyond it; otherwise they recover some stamina.
+    /// Returns whether they're still in the game.
+    fn drive(&mut self, agent: usize, velocity: Vector, sprinting: bool) -> bool {
+        let (velocity, heading) = self.control(self.agents.get(&agent).unwrap().player(), velocity, sprinting);
+        self.travel(agent, velocity, heading, sprinting, 1.)
+    }
+
+    /// The velocity and heading the motion model gives `player` when they ask to move at `velocity`.
+    fn control(&self, player: Player, velocity: Vector, sprinting: bool) -> (Vector, f32) {
+        let speed = if sprinting { self.sprint_speed(player) } else { player.speed };
+        self.motion.integrate(player.velocity, player.heading, velocity, speed)
+    }
+
+    /// Moves an agent at a velocity the motion model has already settled on for `duration`, which must be more than 0.
+    fn travel(&mut self, agent: usize, velocity: Vector, heading: f32, sprinting: bool, duration: f32) -> bool {
+        let player = self.agents.get(&agent).unwrap().player();
+        let stamina = if sprinting {
+            f32::max(0., player.stamina - f32::max(0., length(velocity) - player.speed) * duration)
+        } else {
+            f32::min(player.max_stamina, player.stamina + self.recovery * duration)
+        };
+        let target = Point::new(player.position.x + velocity.x * duration, player.position.y + velocity.y * duration);
+        let mut position = self.stop_short(player.position, target);
+        if self.collisions == Collisions::Block {
+            position = self.stop_at_bodies(player, position);
+        }
+        let moved = Vector::new((position.x - player.position.x) / duration, (position.y - player.position.y) / duration);
+        let (moved, heading) = self.arena.rebound(position, moved, heading);
+        match self.arena.settle(position) {
+            Some(position) => {
+                let mut agent_state = self.agents.get_mut(&agent).unwrap();
+                agent_state.update(&position);
+                agent_state.update_motion(moved, heading);
+                agent_state.update_stamina(stamina);
+                self.index.update(agent, position);
+                true
+            }
+            None => {
+                self.eliminate(agent);
+                false
+            }
+        }
+    }
+
+    /// How far `player` can move towards `to` before bumping into another player's body.
+    /// A player already overlapping someone can move away from them, but not any further in.
+    fn stop_at_bodies(&self, player: Player, to: Point) -> Point {
+        if player.radius + self.max_radius <= 0. {
+            return to;
+        }
+        let from = player.position;
+        let [delta_x, delta_y] = self.arena.delta(from, to);
+        let length = (delta_x * delta_x + delta_y * delta_y).sqrt();
+        let end = Point::new(from.x + delta_x, from.y + delta_y);
+        let hit = self.index.within(from, length + player.radius + self.max_radius).into_iter()
+            .filter(|id| *id != player.id)
+            .map(|id| self.agents.get(&id).unwrap().player())
+            .filter(|other| player.radius + other.radius > 0.)
+            .filter_map(|other| {
+                let [offset_x, offset_y] = self.arena.delta(from, other.position);
+                let centre = Point::new(from.x + offset_x, from.y + offset_y);
+                let radius = player.radius + other.radius;
+                if from.distance(centre) < radius {
+                    let heading_in = delta_x * offset_x + delta_y * offset_y > 0.;
+                    return if heading_in { Some(0.) } else { None };
+                }
+                Obstacle::Circle { centre, radius }.hit(from, end)
+            })
+            .fold(None, |first: Option<f32>, hit| Some(first.map_or(hit, |first| first.min(hit))));
+        match hit {
+            None => to,
+            Some(hit) => {
+                let fraction = f32::max(0., hit - CLEARANCE / length);
+                Point::new(from.x + delta_x * fraction, from.y + delta_y * fraction)
+            }
+        }
+    }
+
+    /// Pushes overlapping players apart, each moving half the overlap directly away from the other;
+    /// if one of them is up against a wall or obstacle, the other moves the rest of the way.
+    /// Pairs are settled in order of id, a few times over, as pushing one pair apart can push others together.
+    fn push_apart(&mut self) {
+        if self.max_radius <= 0. {
+            return;
+        }
+        let mut agents: Vec<usize> = self.agents.iter().map(|agent| *agent.key()).collect();
+        agents.sort_unstable();
+        for _ in 0..PUSH_PASSES {
+            let mut pushed = false;
+            for first in &agents {
+                let position = self.agents.get(first).unwrap().player().position;
+                let radius = self.agents.get(first).unwrap().player().radius;
+                for second in self.index.within(position, radius + self.max_radius) {
+                    if second <= *first {
+                        continue;
+                    }
+                    let one = self.agents.get(first).unwrap().player();
+                    let other = self.agents.get(&second).unwrap().player();
+                    let [delta_x, delta_y] = self.arena.delta(one.position, other.position);
+                    let distance = (delta_x * delta_x + delta_y * delta_y).sqrt();
+                    let overlap = one.radius + other.radius - distance;
+                    if overlap <= TOLERANCE {
+                        continue;
+                    }
+                    // players on the very same spot are pushed apart sideways
+                    let (away_x, away_y) = if distance > 0. { (delta_x / distance, delta_y / distance) } else { (1., 0.) };
+                    let half = overlap / 2.;
+                    let moved = self.shove(one.id, Point::new(one.position.x - away_x * half, one.position.y - away_y * half));
+                    let rest = overlap - moved;
+                    self.shove(other.id, Point::new(other.position.x + away_x * rest, other.position.y + away_y * rest));
+                    pushed = true;
+                }
+            }
+            if !pushed {
+                break;
+            }
+        }
+    }
+
+    /// Moves a player who's been pushed, returning how far they went;
+    /// obstacles still stop them, but a push never takes anyone out of the game.
+    fn shove(&mut self, agent: usize, to: Point) -> f32 {
+        let from = self.agents.get(&agent).unwrap().player().position;
+        let position = self.arena.confine(self.stop_short(from, to));
+        self.agents.get_mut(&agent).unwrap().update(&position);
+        self.index.update(agent, position);
+        self.arena.distance(from, position)
+    }
+
+    /// Applies a set of actions as if they all happened at once, as part of a step finished by `end_step`;
+    /// see `step_all`.
+    pub(crate) fn resolve(&mut self, actions: Vec<(usize, Action)>, rng: &mut StdRng, report: &mut StepReport) {
+        let mut tags: BTreeMap<usize, Vec<(usize, Point)>> = BTreeMap::new();
+        let mut moves: Vec<(usize, Vector, bool)> = Vec::with_capacity(actions.len());
+        let mut pick_ups: BTreeMap<usize, Vec<(usize, Point)>> = BTreeMap::new();
+        let mut drops: Vec<usize> = Vec::new();
+        for (agent, action) in actions.iter() {
+            let agent = *agent;
+            match self.validate(agent, action) {
+                Err(rejection) => {
+                    self.reject(agent, action, rejection);
+                    report.rejections.push((agent, rejection));
+                }
+                Ok(()) => match action {
+                    Action::Tag(target) => tags.entry(*target).or_default().push((agent, self.agents.get(&agent).unwrap().player().position)),
+                    Action::Move(position) => moves.push((agent, self.toward(agent, *position), false)),
+                    Action::Steer(velocity) => moves.push((agent, *velocity, false)),
+                    Action::Sprint(velocity) => moves.push((agent, *velocity, true)),
+                    Action::MoveAndTag(position, target) => {
+                        let from = self.destination(self.agents.get(&agent).unwrap().player(), *position).unwrap();
+                        tags.entry(*target).or_default().push((agent, from));
+                        moves.push((agent, self.toward(agent, *position), false));
+                    }
+                    Action::PickUp(team) => pick_ups.entry(*team).or_default().push((agent, self.agents.get(&agent).unwrap().player().position)),
+                    Action::Drop => drops.push(agent),
+                }
+            }
+        }
+
+        let mut caught: HashMap<usize, usize> = HashMap::with_capacity(tags.len());
+        for (target, attempts) in tags {
+            let mut taggers: Vec<(usize, Point)> = Vec::with_capacity(attempts.len());
+            for (tagger, from) in attempts {
+                if self.missed(tagger, from, target, rng) {
+                    report.misses.push((tagger, target));
+                } else {
+                    taggers.push((tagger, from));
+                }
+            }
+            if taggers.is_empty() {
+                continue;
+            }
+            let winner = self.pick_winner(self.agents.get(&target).unwrap().player().position, &taggers, rng);
+            if taggers.len() > 1 {
+                let losers: Vec<usize> = taggers.into_iter().map(|(tagger, _)| tagger).filter(|tagger| *tagger != winner).collect();
+                log::info!("Agents {:?} and {:?} tagged {:?} at once; {:?} wins.", winner, losers, target, winner);
+                report.conflicts.push(Conflict::SharedTarget { target, winner, losers });
+            }
+            if self.frozen.contains(&target) {
+                self.apply(winner, &Action::Tag(target));
+                report.rescues.push((winner, target));
+                continue;
+            }
+            self.apply(winner, &Action::Tag(target));
+            report.tags.push((winner, target));
+            caught.insert(target, winner);
+        }
+        self.handle_flags(pick_ups, drops, &caught, rng, report);
+
+        for (agent, velocity, sprinting) in moves {
+            match caught.get(&agent) {
+                None => if !self.drive(agent, velocity, sprinting) {
+                    report.eliminated.push(agent);
+                },
+                Some(tagger) => {
+                    log::debug!("Agent {:?} was caught by {:?} before moving.", agent, tagger);
+                    report.conflicts.push(Conflict::CaughtMoving { target: agent, tagger: *tagger });
+                }
+            }
+        }
+        if self.collisions == Collisions::Push {
+            self.push_apart();
+        }
+    }
+
+    /// Applies one agent's action straight away, as part of a step finished by `end_step`.
+    /// An agent who has left the game since choosing their action, such as by being tagged out, no longer acts.
+    pub(crate) fn activate(&mut self, agent: usize, action: Action, rng: &mut StdRng, report: &mut StepReport) {
+        if !self.agents.contains_key(&agent) {
+            log::debug!("Agent {:?} has left the game, so its action {:?} is dropped.", agent, action);
+            return;
+        }
+        let rescuing = matches!(action, Action::Tag(target) | Action::MoveAndTag(_, target) if self.frozen.contains(&target));
+        if let Err(rejection) = self.step(agent, &action, rng) {
+            report.rejections.push((agent, rejection));
+            return;
+        }
+        if let Action::Tag(target) | Action::MoveAndTag(_, target) = action {
+            if rescuing && !self.frozen.contains(&target) {
+                report.rescues.push((agent, target));
+            } else if !rescuing && self.caught(agent, target) {
+                report.tags.push((agent, target));
+            } else {
+                report.misses.push((agent, target));
+            }
+        }
+        if !self.agents.contains_key(&agent) {
+            report.eliminated.push(agent);
+        }
+    }
+
+    /// Makes the drops and then the pick-ups of flags from a step, already checked against the start of the step.
+    /// Those caught in the step, given as target and tagger, don't get to pick up a flag, and neither does anyone the
+    /// step has left unable to; of several players going for the same flag, the conflict policy picks who gets it.
+    /// The players going for each flag are given in ascending order of id, along with where they are.
+    fn handle_flags(&mut self, pick_ups: BTreeMap<usize, Vec<(usize, Point)>>, drops: Vec<usize>, caught: &HashMap<usize, usize>,
+                    rng: &mut StdRng, report: &mut StepReport) {
+        for agent in drops {
+            self.drop_flag(agent);
+        }
+        for (team, attempts) in pick_ups {
+            let mut players: Vec<(usize, Point)> = Vec::with_capacity(attempts.len());
+            for (agent, from) in attempts {
+                if let Some(tagger) = caught.get(&agent) {
+                    report.conflicts.push(Conflict::CaughtMoving { target: agent, tagger: *tagger });
+                } else if self.validate(agent, &Action::PickUp(team)).is_ok() {
+                    players.push((agent, from));
+                } else {
+                    log::debug!("Agent {:?} can no longer pick up team {:?}'s flag.", agent, team);
+                }
+            }
+            if players.is_empty() {
+                continue;
+            }
+            let winner = self.pick_winner(self.flags[team].position, &players, rng);
+            if players.len() > 1 {
+                let losers: Vec<usize> = players.into_iter().map(|(player, _)| player).filter(|player| *player != winner).collect();
+                log::info!("Agents {:?} and {:?} went for team {:?}'s flag at once; {:?} gets it.", winner, losers, team, winner);
+                report.conflicts.push(Conflict::SharedFlag { team, winner, losers });
+            }
+            self.apply(winner, &Action::PickUp(team));
+        }
+    }
+
+    /// Scores a point for each team carrying the other team's flag on their own side, sending the flag back to its stand.
+    fn score(&mut self, report: &mut StepReport) {
+        for index in 0..self.flags.len() {
+            let carrier = match self.flags[index].carrier {
+                None => continue,
+                Some(carrier) => self.agents.get(&carrier).unwrap().player(),
+            };
+            if self.is_protected(carrier) {
+                self.flags[index].return_home();
+                self.scores[carrier.team] += 1;
+                report.captures.push((carrier.id, self.flags[index].team));
+                log::info!("Agent {:?} has captured team {:?}'s flag for team {:?}.", carrier.id, self.flags[index].team, carrier.team);
+            }
+        }
+    }
+
+    /// Finishes a step, scoring any flags carried home and moving the game on by a unit of time.
+    pub(crate) fn end_step(&mut self, report: &mut StepReport) {
+        self.score(report);
+        self.steps += 1;
+        self.time += 1.;
+        report.elapsed = 1.;
+    }
+
+    /// Whether a tag by `tagger` on `target` has landed, going by what a tag does in the game being played.
+    fn caught(&self, tagger: usize, target: usize) -> bool {
+        match (self.mode, self.capture) {
+            (GameMode::Classic, _) | (GameMode::Infection, _) => self.it.contains(&target),
+            (GameMode::Freeze, _) | (GameMode::Teams | GameMode::Flag, Capture::Jail) => self.frozen.contains(&target),
+            (GameMode::Elimination, _) => !self.agents.contains_key(&target),
+            (GameMode::Teams | GameMode::Flag, Capture::Switch) => {
+                let team = |agent: usize| self.agents.get(&agent).map(|agent| agent.player().team);
+                team(tagger) == team(target)
+            }
+        }
+    }
+
+    /// Takes a player who went out of bounds out of the game.
+    fn eliminate(&mut self, agent: usize) {
+        log::info!("Agent {:?} went out of bounds and is out of the game.", agent);
+        self.remove_agent(agent);
+    }
+
+    /// Rolls for whether a tag by `tagger` from the point `from` misses, according to the rules.
+    fn missed(&self, tagger: usize, from: Point, target: usize, rng: &mut StdRng) -> bool {
+        let tagger = self.agents.get(&tagger).unwrap().player();
+        let target = self.agents.get(&target).unwrap().player();
+        let chance = self.rules.miss_chance(self.arena.distance(from, target.position), self.tag_range(tagger, target));
+        let missed = chance > 0. && rng.gen_bool(chance);
+        if missed {
+            log::info!("Agent {:?} missed tagging agent {:?}.", tagger.id, target.id);
+        }
+        missed
+    }
+
+    fn reject(&mut self, agent: usize, action: &Action, rejection: Rejection) {
+        log::warn!("Rejected action {:?} from agent {:?}: {:?}", action, agent, rejection);
+        *self.violations.entry(agent).or_insert(0) += 1;
+    }
+
+    /// Picks which of the players going for the same target at once, whether a player to tag or a flag to pick up,
+    /// gets it. The players are given in ascending order of id, along with where they are going for it from.
+    fn pick_winner(&self, target: Point, taggers: &[(usize, Point)], rng: &mut StdRng) -> usize {
+        match self.conflict_policy {
+            ConflictPolicy::EarliestIndex => taggers[0].0,
+            ConflictPolicy::Random => taggers[rng.gen_range(0, taggers.len())].0,
+            ConflictPolicy::ClosestTagger => {
+                taggers.iter()
+                    .min_by(|(a, a_from), (b, b_from)| {
+                        self.arena.distance(*a_from, target).partial_cmp(&self.arena.distance(*b_from, target)).unwrap().then(a.cmp(b))
+                    })
+                    .unwrap().0
+            }
+        }
+    }
+
+    /// Checks an action against the rules of the game, without applying it.
+    pub fn validate(&self, agent: usize, action: &Action) -> Result<(), Rejection> {
+        let actor = match self.agents.get(&agent) {
+            None => return Err(Rejection::UnknownAgent(agent)),
+            Some(actor) => actor.player(),
+        };
+        let stuck = self.frozen.contains(&agent);
+        match action {
+            Action::Tag(_) | Action::MoveAndTag(..) | Action::PickUp(_) if stuck => Err(Rejection::Stuck),
+            Action::Move(position) if stuck && self.arena.distance(actor.position, *position) > TOLERANCE => Err(Rejection::Stuck),
+            Action::Steer(velocity) | Action::Sprint(velocity) if stuck && length(*velocity) > TOLERANCE => Err(Rejection::Stuck),
+            Action::Tag(other) => self.validate_tag(actor, actor.position, *other),
+            Action::Move(position) => self.validate_move(actor, *position),
+            Action::Steer(velocity) => self.validate_velocity(*velocity, actor.speed),
+            Action::Sprint(velocity) => self.validate_velocity(*velocity, f32::max(actor.speed, actor.sprint_speed)),
+            Action::MoveAndTag(position, other) => {
+                self.validate_move(actor, *position)?;
+                let from = self.destination(actor, *position).ok_or(Rejection::OutOfBounds(*position))?;
+                self.validate_tag(actor, from, *other)
+            }
+            Action::PickUp(team) => self.validate_pick_up(actor, *team),
+            Action::Drop if self.carrying(agent).is_none() => Err(Rejection::NotCarrying),
+            Action::Drop => Ok(()),
+        }
+    }
+
+    /// Checks a pick-up by `actor` of `team`'s flag.
+    fn validate_pick_up(&self, actor: Player, team: usize) -> Result<(), Rejection> {
+        let flag = self.flags.get(team).ok_or(Rejection::UnknownFlag(team))?;
+        let distance = self.arena.distance(actor.position, flag.position);
+        if flag.team == actor.team {
+            Err(Rejection::OwnFlag(team))
+        } else if flag.carrier.is_some() {
+            Err(Rejection::FlagTaken(team))
+        } else if distance > actor.reach + TOLERANCE {
+            Err(Rejection::OutOfReach { distance, reach: actor.reach })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn validate_velocity(&self, velocity: Vector, speed: f32) -> Result<(), Rejection> {
+        let distance = length(velocity);
+        if distance > speed + TOLERANCE { Err(Rejection::TooFar { distance, speed }) } else { Ok(()) }
+    }
+
+    /// Checks a tag by `actor` on `other` made from the point `from`.
+    fn validate_tag(&self, actor: Player, from: Point, other: usize) -> Result<(), Rejection> {
+        let target = match self.agents.get(&other) {
+            None => return Err(Rejection::UnknownTarget(other)),
+            Some(target) => target.player(),
+        };
+        if self.can_rescue(actor, target) {
+            self.validate_contact(actor, from, target)
+        } else if self.mode.has_teams() {
+            self.validate_team_tag(actor, from, target)
+        } else if !actor.is_it {
+            Err(Rejection::NotIt)
+        } else if target.id == actor.id {
+            Err(Rejection::SelfTag)
+        } else if target.is_it {
+            Err(Rejection::AlreadyIt(target.id))
+        } else if self.frozen.contains(&other) {
+            Err(Rejection::Frozen(target.id))
+        } else if self.is_tag_back(actor, target) {
+            Err(Rejection::TagBack(target.id))
+        } else if self.is_immune(target) {
+            Err(Rejection::Immune(target.id))
+        } else {
+            self.validate_contact(actor, from, target)
+        }
+    }
+
+    /// Checks a tag in a team game on someone other than a jailed teammate.
+    fn validate_team_tag(&self, actor: Player, from: Point, target: Player) -> Result<(), Rejection> {
+        if target.id == actor.id {
+            Err(Rejection::SelfTag)
+        } else if target.team == actor.team {
+            Err(Rejection::Teammate(target.id))
+        } else if self.frozen.contains(&target.id) {
+            Err(Rejection::Frozen(target.id))
+        } else if self.is_protected(target) {
+            Err(Rejection::Protected(target.id))
+        } else if self.is_immune(target) {
+            Err(Rejection::Immune(target.id))
+        } else {
+            self.validate_contact(actor, from, target)
+        }
+    }
+
+    /// Checks that `actor` can touch `target` from the point `from`: close enough, and with nothing in the way.
+    fn validate_contact(&self, actor: Player, from: Point, target: Player) -> Result<(), Rejection> {
+        let distance = self.arena.distance(from, target.position);
+        let range = self.tag_range(actor, target);
+        if distance > range + TOLERANCE {
+            Err(Rejection::OutOfReach { distance, reach: range })
+        } else if !self.in_sight(from, target.position) {
+            Err(Rejection::OutOfSight(target.id))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// What's left of an agent's stamina, and the most they can hold.
+    pub fn stamina(&self, agent: usize) -> Option<(f32, f32)> {
+        self.agents.get(&agent).map(|agent| (agent.player().stamina, agent.player().max_stamina))
+    }
+
+    /// The furthest `player` could go in a sprinting step, given their stamina.
+    pub fn sprint_speed(&self, player: Player) -> f32 {
+        f32::max(player.speed, f32::min(player.sprint_speed, player.speed + player.stamina))
+    }
+
+    /// Whether the rules allow `tagger` to tag `target`, leaving aside how far apart they are.
+    /// In team games, anyone not in jail can tag an opponent who is on the tagger's side of the field.
+    pub fn can_tag(&self, tagger: Player, target: Player) -> bool {
+        if self.mode.has_teams() {
+            return tagger.team != target.team && !self.frozen.contains(&tagger.id) && !self.frozen.contains(&target.id)
+                && !self.is_protected(target) && !self.is_immune(target);
+        }
+        tagger.is_it && !target.is_it && tagger.id != target.id && !self.frozen.contains(&target.id)
+            && !self.is_tag_back(tagger, target) && !self.is_immune(target)
+    }
+
+    /// Whether `rescuer` is free to unfreeze `target` (or, in team games, let them out of jail),
+    /// leaving aside how far apart they are.
+    pub fn can_rescue(&self, rescuer: Player, target: Player) -> bool {
+        let teammate = if self.mode.has_teams() { rescuer.team == target.team } else { !rescuer.is_it };
+        teammate && !self.frozen.contains(&rescuer.id) && self.frozen.contains(&target.id)
+    }
+
+    /// Whether `player` is on their own side of the field in a team game, where they can't be tagged.
+    pub fn is_protected(&self, player: Player) -> bool {
+        if !self.mode.has_teams() {
+            return false;
+        }
+        let (left, right) = home_side(player.team, self.arena.width);
+        player.position.x >= left && player.position.x < right
+    }
+
+    /// Where `team` holds the opponents they've caught in a team game: on their own side, near the far edge.
+    pub fn jail(&self, team: usize) -> Point {
+        self.inset_from_edge(team, JAIL_INSET)
+    }
+
+    /// Where `team`'s flag stands in capture the flag: on their own side, a little in front of their jail.
+    pub fn flag_stand(&self, team: usize) -> Point {
+        self.inset_from_edge(team, FLAG_INSET)
+    }
+
+    /// The point half way down the field, `inset` (a share of the field's width) in from the far edge of `team`'s side.
+    fn inset_from_edge(&self, team: usize, inset: f32) -> Point {
+        let (left, right) = home_side(team, self.arena.width);
+        let inset = inset * self.arena.width;
+        let x = if team == 0 { left + inset } else { right - inset };
+        Point::new(x, self.arena.height / 2.)
+    }
+
+    /// The team whose flag `agent` is carrying, if they're carrying one.
+    pub fn carrying(&self, agent: usize) -> Option<usize> {
+        self.flags.iter().find(|flag| flag.carrier == Some(agent)).map(|flag| flag.team)
+    }
+
+    /// Where a flag is: with whoever is carrying it, or wherever it was put down.
+    pub fn flag_position(&self, flag: &Flag) -> Point {
+        flag.carrier.and_then(|carrier| self.agents.get(&carrier)).map_or(flag.position, |carrier| carrier.player().position)
+    }
+
+    /// Whether `tagger` could reach `target` from the point `from`: close enough, and with nothing in the way.
+    pub fn within_reach(&self, tagger: Player, from: Point, target: Player) -> bool {
+        self.arena.distance(from, target.position) <= self.tag_range(tagger, target) && self.in_sight(from, target.position)
+    }
+
+    /// How close `tagger` has to be to `target` to tag them.
+    pub fn tag_range(&self, tagger: Player, target: Player) -> f32 {
+        match self.rules.hit_test {
+            HitTest::Center => tagger.reach,
+            HitTest::Overlap => tagger.reach + target.reach,
+        }
+    }
+
+    /// The furthest `tagger` could be from anyone they can tag.
+    pub fn max_tag_range(&self, tagger: Player) -> f32 {
+        match self.rules.hit_test {
+            HitTest::Center => tagger.reach,
+            HitTest::Overlap => tagger.reach + self.max_reach,
+        }
+    }
+
+    fn is_tag_back(&self, tagger: Player, target: Player) -> bool {
+        let since_tagged = self.steps.saturating_sub(*self.tagged_at.get(&tagger.id).unwrap_or(&0));
+        target.id == tagger.last_tagged && since_tagged < self.rules.tag_back_cooldown
+    }
+
+    fn is_immune(&self, target: Player) -> bool {
+        match self.released_at.get(&target.id) {
+            None => false,
+            Some(released) => self.steps - released < self.rules.immunity,
+        }
+    }
+
+    /// Checks a move by `actor` to `position`.
+    /// Only the clamp policy refuses moves off the field; the others decide where the player ends up.
+    fn validate_move(&self, actor: Player, position: Point) -> Result<(), Rejection> {
+        let distance = self.arena.distance(actor.position, position);
+        if self.arena.boundary == Boundary::Clamp && !self.arena.contains(position) {
+            Err(Rejection::OutOfBounds(position))
+        } else if distance > actor.speed + TOLERANCE {
+            Err(Rejection::TooFar { distance, speed: actor.speed })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Where `player` heading for `to` ends up under the motion model, stopping short of any obstacle in the way,
+    /// or `None` if they leave the game.
+    pub fn destination(&self, player: Player, to: Point) -> Option<Point> {
+        let [delta_x, delta_y] = self.arena.delta(player.position, to);
+        let (velocity, _) = self.motion.integrate(player.velocity, player.heading, Vector::new(delta_x, delta_y), player.speed);
+        self.arena.settle(self.stop_short(player.position, player.position + velocity))
+    }
+
+    /// How far along the shortest line from `from` to `to` a player gets before stopping short of an obstacle,
+    /// before the boundary policy is applied.
+    fn stop_short(&self, from: Point, to: Point) -> Point {
+        let [delta_x, delta_y] = self.arena.delta(from, to);
+        let length = (delta_x * delta_x + delta_y * delta_y).sqrt();
+        let fraction = match self.first_hit(from, to) {
+            None => 1.,
+            Some(hit) => f32::max(0., hit - CLEARANCE / length),
+        };
+        Point::new(from.x + delta_x * fraction, from.y + delta_y * fraction)
+    }
+
+    /// Whether there are no obstacles on the line between two points.
+    pub fn in_sight(&self, from: Point, to: Point) -> bool {
+        self.first_hit(from, to).is_none()
+    }
+
+    /// A point to move to in place of `to` that gets around any obstacle in the way, found by turning further and further
+    /// away from the direct line; if there's no way round, the player stays put.
+    pub fn steer(&self, from: Point, to: Point) -> Point {
+        if self.in_sight(from, to) {
+            return to;
+        }
+        let [delta_x, delta_y] = self.arena.delta(from, to);
+        DETOURS.iter()
+            .map(|degrees| {
+                let (sin, cos) = degrees.to_radians().sin_cos();
+                self.arena.confine(Point::new(from.x + delta_x * cos - delta_y * sin, from.y + delta_x * sin + delta_y * cos))
+            })
+            .find(|detour| self.in_sight(from, *detour))
+            .unwrap_or(from)
+    }
+
+    /// How far along the shortest line from `from` to `to` (as a fraction of its length) it first runs into an obstacle.
+    /// On a wrapping field, the obstacles' copies over the edges are checked too.
+    fn first_hit(&self, from: Point, to: Point) -> Option<f32> {
+        if self.obstacles.is_empty() {
+            return None;
+        }
+        let [delta_x, delta_y] = self.arena.delta(from, to);
+        let mut first: Option<f32> = None;
+        for [shift_x, shift_y] in self.arena.shifts() {
+            let start = Point::new(from.x - shift_x, from.y - shift_y);
+            let end = Point::new(start.x + delta_x, start.y + delta_y);
+            for hit in self.obstacles.iter().filter_map(|obstacle| obstacle.hit(start, end)) {
+                first = Some(first.map_or(hit, |first| first.min(hit)));
+            }
+        }
+        first
+    }
+
+    /// The number of actions from the given agent that have been rejected since the last reset.
+    pub fn violations(&self, agent: usize) -> usize {
+        *self.violations.get(&agent).unwrap_or(&0)
+    }
+
+    /// All agents within `radius` of `point`, in ascending order of id.
+    pub fn neighbours_within(&self, point: Point, radius: f32) -> Vec<P> {
+        self.index.within(point, radius).iter()
+            .map(|id| *self.agents.get(id).unwrap())
+            .collect()
+    }
+
+    /// The first contact within `horizon` between an 'it' and someone they can tag, with everyone moving in a straight
+    /// line at their velocity, as (time, tagger, target); ties are broken by the lower tagger id, then target id.
+    /// Players in `targeted` have already been caught up with, and aren't targets again.
+    fn next_contact(&self, velocities: &HashMap<usize, Vector>, horizon: f32, targeted: &HashSet<usize>) -> Option<(f32, usize, usize)> {
+        let still = Vector::new(0., 0.);
+        // in team games, everyone is a tagger
+        let mut its: Vec<usize> = if self.mode.has_teams() {
+            self.agents.iter().map(|agent| *agent.key()).collect()
+        } else {
+            self.it.iter().copied().collect()
+        };
+        its.sort_unstable();
+        let mut first: Option<(f32, usize, usize)> = None;
+        for it in its {
+            let tagger = self.agents.get(&it).unwrap().player();
+            let tagger_velocity = *velocities.get(&it).unwrap_or(&still);
+            for agent in self.agents.iter() {
+                let target = agent.player();
+                if targeted.contains(&target.id) || !self.can_tag(tagger, target) {
+                    continue;
+                }
+                let target_velocity = *velocities.get(&target.id).unwrap_or(&still);
+                let relative = target_velocity - tagger_velocity;
+                let horizon = first.map_or(horizon, |(time, _, _)| time);
+                let time = match contact_time(self.arena.delta(tagger.position, target.position), relative, self.tag_range(tagger, target), horizon) {
+                    None => continue,
+                    Some(time) => time,
+                };
+                let from = tagger.position + Vector::new(tagger_velocity.x * time, tagger_velocity.y * time);
+                let to = target.position + Vector::new(target_velocity.x * time, target_velocity.y * time);
+                if !self.in_sight(from, to) {
+                    continue;
+                }
+                let earlier = match first {
+                    None => true,
+                    Some(first) => (time, it, target.id) < first,
+                };
+                if earlier {
+                    first = Some((time, it, target.id));
+                }
+            }
+        }
+        first
+    }
+
+    /// An id no agent in the game has, for a new player joining; ids of players who have left aren't reused.
+    pub fn next_id(&self) -> usize {
+        self.issued_ids
+    }
+
+    /// The players who have won, once the game is won: in classic tag, the last player left in when everyone else
+    /// has gone out of bounds; in freeze tag, the 'it's, once every runner is frozen; in infection tag, the last to be
+    /// infected, once everyone is; in elimination tag, the last runner standing, if any; in team tag, the last team
+    /// with anyone free; in capture the flag, the first team to capture enough flags.
+    pub fn winners(&self) -> Option<Vec<usize>> {
+        match self.mode {
+            GameMode::Classic if self.issued_ids > 1 && self.agents.len() == 1 => Some(self.agents.iter().map(|agent| *agent.key()).collect()),
+            GameMode::Freeze if !self.frozen.is_empty() && self.agents.len() == self.it.len() + self.frozen.len() => {
+                let mut its: Vec<usize> = self.it.iter().copied().collect();
+                its.sort_unstable();
+                Some(its)
+            }
+            GameMode::Elimination if self.agents.len() <= self.it.len() + 1 => Some(self.leaders()),
+            GameMode::Infection if !self.agents.is_empty() && self.it.len() == self.agents.len() => {
+                let last = self.it.iter().filter_map(|id| self.tagged_at.get(id)).max();
+                let mut survivors: Vec<usize> = self.it.iter().copied().filter(|id| self.tagged_at.get(id) == last).collect();
+                survivors.sort_unstable();
+                Some(survivors)
+            }
+            GameMode::Teams => {
+                let free = self.free_by_team();
+                let standing: Vec<usize> = (0..TEAMS).filter(|team| free[*team] > 0).collect();
+                match standing[..] {
+                    [team] => Some(self.team_members(team)),
+                    _ => None,
+                }
+            }
+            GameMode::Flag if self.flags_to_win > 0 => {
+                (0..TEAMS).find(|team| self.scores[*team] >= self.flags_to_win).map(|team| self.team_members(team))
+            }
+            _ => None,
+        }
+    }
+
+    /// The players who would win if the game stopped now without having been won: everyone left in who isn't 'it'
+    /// (or frozen); in team tag, the team (or teams) with the most players free; in capture the flag, the team
+    /// (or teams) with the most flags captured.
+    pub fn leaders(&self) -> Vec<usize> {
+        if self.mode.has_teams() {
+            let standing = if self.mode == GameMode::Flag { self.scores } else { self.free_by_team() };
+            let most = standing.iter().copied().max().unwrap_or(0);
+            let mut leaders: Vec<usize> = (0..TEAMS).filter(|team| standing[*team] == most).flat_map(|team| self.team_members(team)).collect();
+            leaders.sort_unstable();
+            return leaders;
+        }
+        let mut leaders: Vec<usize> = self.agents.iter().map(|agent| *agent.key())
+            .filter(|id| !self.it.contains(id) && !self.frozen.contains(id))
+            .collect();
+        leaders.sort_unstable();
+        leaders
+    }
+
+    /// The number of players on each team who aren't in jail.
+    fn free_by_team(&self) -> [usize; TEAMS] {
+        let mut free = [0; TEAMS];
+        for agent in self.agents.iter().filter(|agent| !self.frozen.contains(agent.key())) {
+            free[agent.player().team] += 1;
+        }
+        free
+    }
+
+    /// The ids of the players on `team`, in order.
+    pub fn team_members(&self, team: usize) -> Vec<usize> {
+        let mut members: Vec<usize> = self.agents.iter().filter(|agent| agent.player().team == team).map(|agent| *agent.key()).collect();
+        members.sort_unstable();
+        members
+    }
+
+    /// The nearest agent to `point` that satisfies `predicate`, with ties broken by the lower id.
+    pub fn nearest_matching<F>(&self, point: Point, predicate: F) -> Option<P>
+        where
+            F: Fn(&P) -> bool
+    {
+        self.index.nearest(point, |id| predicate(self.agents.get(&id).unwrap().value()))
+            .map(|(id, _)| *self.agents.get(&id).unwrap())
+    }
+
     fn draw_frame(&self, frame: &mut canvas::Frame) {
         let space = Path::rectangle(Point::new(0.0, 0.0), frame.size());
         frame.fill(&space, Color::BLACK);
 
+        if self.mode == GameMode::Flag {
+            // each team's territory is tinted with their colour
+            for team in 0..TEAMS {
+                let (left, right) = home_side(team, self.arena.width);
+                frame.fill(&Path::rectangle(Point::new(left, 0.), Size::new(right - left, self.arena.height)), team_colour(team, 0.15));
+            }
+        }
+
+        for obstacle in &self.obstacles {
+            let path = obstacle.path();
+            for [shift_x, shift_y] in self.arena.shifts() {
+                frame.with_save(|frame| {
+                    frame.translate(Vector::new(shift_x, shift_y));
+                    frame.fill(&path, Color::from_rgb8(0x60, 0x60, 0x60));
+                });
+            }
+        }
+
+        if self.mode.has_teams() {
+            for team in 1..TEAMS {
+                let (left, _) = home_side(team, self.arena.width);
+                frame.fill(&Path::rectangle(Point::new(left - 1., 0.), Size::new(2., self.arena.height)), Color::from_rgb8(0x60, 0x60, 0x60));
+            }
+            if self.capture == Capture::Jail {
+                for team in 0..TEAMS {
+                    frame.fill(&Path::circle(self.jail(team), JAIL_INSET * self.arena.width / 2.), Color::from_rgb8(0x30, 0x30, 0x30));
+                }
+            }
+        }
+
         for agent in &self.agents {
+            let player = agent.player();
+            let jailed = self.frozen.contains(&player.id);
+            let colour = if self.mode.has_teams() {
+                // jailed players are drawn in a darker shade of their team's colour
+                team_colour(player.team, if jailed { 0.5 } else { 1. })
+            } else if player.is_it {
+                Color::from_rgb8(0xF9, 0xD7, 0x1C)
+            } else if jailed {
+                Color::from_rgb8(0x5B, 0xC0, 0xEB)
+            } else {
+                Color::WHITE
+            };
+            // on a wrapping field, players partly over an edge are drawn again over the opposite edge
+            for image in self.arena.images(player.position, player.reach) {
+                frame.fill(&Path::circle(image, player.reach), colour);
+            }
             if self.show_numbers {
                 frame.fill_text(canvas::Text {
                     content: agent.player().id.to_string(),
@@ -126,119 +1318,539 @@ impl<P> TagEnvironment<P>
                     ..canvas::Text::default()
                 });
             }
-            if !agent.player().is_it {
-                frame.fill(&Path::circle(agent.player().position, agent.player().reach), Color::WHITE);
-            } else {
-                frame.fill(&Path::circle(agent.player().position, agent.player().reach), Color::from_rgb8(0xF9, 0xD7, 0x1C));
-            }
+        }
+
+        // flags are drawn over the players, so that a carried flag shows on its carrier
+        for flag in &self.flags {
+            frame.fill(&Path::circle(flag.home, FLAG_SIZE / 4.), Color::from_rgb8(0x60, 0x60, 0x60));
+            let base = self.flag_position(flag);
+            let pennant = Path::new(|builder| {
+                builder.move_to(base);
+                builder.line_to(Point::new(base.x, base.y - FLAG_SIZE));
+                builder.line_to(Point::new(base.x + FLAG_SIZE * 0.75, base.y - FLAG_SIZE * 0.75));
+                builder.line_to(Point::new(base.x, base.y - FLAG_SIZE / 2.));
+                builder.close();
+            });
+            frame.fill(&pennant, team_colour(flag.team, 1.));
         }
 
     }
 
 }
 
+/// The colour `team`'s players are drawn in, darkened to `brightness`, from 0 (black) to 1 (full colour).
+fn team_colour(team: usize, brightness: f32) -> Color {
+    let Color { r, g, b, a } = if team == 0 { Color::from_rgb8(0xE8, 0x4A, 0x5F) } else { Color::from_rgb8(0x2A, 0x9D, 0x8F) };
+    Color { r: r * brightness, g: g * brightness, b: b * brightness, a }
+}
 
 #[cfg(test)]
 mod tests {
     use crate::tag_environment::TagEnvironment;
-    use dashmap::DashMap;
     use crate::environment::Environment;
-    use crate::action::Action;
-    use iced::Point;
+    use crate::action::{Action, Rejection};
+    use iced::{Point, Vector};
     use crate::agents::agent::{Player, Agent};
-    use crate::parameters::DEFAULT_PARAMS;
-    use std::collections::HashSet;
+    use crate::parameters::{DEFAULT_PARAMS, TagParams};
     use crate::action::Action::Tag;
+    use crate::resolution::{ConflictPolicy, Conflict};
+    use std::collections::HashSet;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::rules::{TagRules, HitTest, DEFAULT_RULES};
+    use crate::arena::Boundary;
+    use crate::obstacles::Obstacle;
+    use crate::collisions::Collisions;
+    use crate::motion::{Motion, MotionModel, DEFAULT_MOTION};
+    use crate::game_mode::{GameMode, Capture};
 
     #[test]
     fn can_setup_env() {
         let mut env: TagEnvironment<Player> = base_env();
         assert_eq!(3, env.agents.len());
-        let agent2_dupe: Player = Player {
-            id: 2,
-            is_it: true,
-            last_tagged: 1,
-            position: Point {
-                x: 1.5,
-                y: 1.0,
-            },
-            speed: 1.0,
-            reach: 2.0
-        };
+        let agent2_dupe = Player { is_it: true, last_tagged: 1, speed: 1.0, sprint_speed: 1.0, ..player(2, 1.5, 1.) };
         env.add_agent(agent2_dupe);
         assert_eq!(3, env.agents.len());
         assert_eq!(&agent2_dupe, env.agents.get(&2).unwrap().value());
-        &env.reset(DEFAULT_PARAMS);
+        env.reset(&DEFAULT_PARAMS, &mut StdRng::seed_from_u64(0));
         assert_eq!(DEFAULT_PARAMS.num_players, env.agents.len());
     }
 
     #[test]
     fn updates_properly() {
         let mut env: TagEnvironment<Player> = base_env();
-        let mut actions: Vec<Action> = Vec::with_capacity(env.agents.len());
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut actions: Vec<(usize, Action)> = Vec::with_capacity(env.agents.len());
         for agent in 0..env.agents.len() {
-            actions.insert(agent, env.agents.get(&agent).unwrap().act(&env));
+            actions.push((agent, env.agents.get(&agent).unwrap().act(&env, &mut rng)));
         }
-        env.step_all(actions);
-        assert_ne!(true, env.agents.get(&0).unwrap().is_it);
+        env.step_all(actions, &mut rng);
+        assert!(!env.agents.get(&0).unwrap().is_it);
     }
 
     fn base_env() -> TagEnvironment<Player> {
-        let mut env: TagEnvironment<Player> = TagEnvironment {
-            agents: DashMap::with_capacity(3),
-            width: 2.,
-            height: 2.,
-            it: HashSet::new(),
-            show_numbers: false
-        };
-        let agent0: Player = Player {
-            id: 0,
-            is_it: true,
-            last_tagged: 0,
-            position: Point {
-                x: 0.0,
-                y: 0.0,
-            },
-            speed: 2.0,
-            reach: 2.0
-        };
-        let agent1: Player = Player {
-            id: 1,
-            is_it: false,
-            last_tagged: 1,
-            position: Point {
-                x: 0.5,
-                y: 0.5,
-            },
-            speed: 2.0,
-            reach: 2.0
-        };
-        let agent2: Player = Player {
-            id: 2,
-            is_it: false,
-            last_tagged: 2,
-            position: Point {
-                x: 1.0,
-                y: 1.0,
-            },
-            speed: 2.0,
-            reach: 2.0
-        };
+        bounded_env(Boundary::Clamp)
+    }
+
+    fn bounded_env(boundary: Boundary) -> TagEnvironment<Player> {
+        let mut env: TagEnvironment<Player> = TagEnvironment::new(&TagParams {
+            width: 2,
+            height: 2,
+            num_players: 3,
+            boundary,
+            ..DEFAULT_PARAMS
+        });
+        let agent0 = Player { is_it: true, ..player(0, 0., 0.) };
+        let agent1 = player(1, 0.5, 0.5);
+        let agent2 = player(2, 1., 1.);
         env.it.insert(0);
-        env.add_agent( agent0);
-        env.add_agent( agent1);
-        env.add_agent( agent2);
+        env.add_agent(agent0);
+        env.add_agent(agent1);
+        env.add_agent(agent2);
         env
     }
 
+    /// A runner at (x, y) with speed and reach 2 and no body, last tagged by themself.
+    fn player(id: usize, x: f32, y: f32) -> Player {
+        Player {
+            id,
+            is_it: false,
+            last_tagged: id,
+            position: Point::new(x, y),
+            speed: 2.0,
+            reach: 2.0,
+            radius: 0.0,
+            velocity: Vector::new(0., 0.),
+            heading: 0.0,
+            sprint_speed: 2.0,
+            stamina: 0.0,
+            max_stamina: 0.0,
+            team: 0
+        }
+    }
+
+    #[test]
+    fn freezes_and_rescues() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = base_env();
+        env.mode = GameMode::Freeze;
+        let report = env.step_all(vec![(0, Tag(1))], &mut rng);
+        assert_eq!(vec![(0, 1)], report.tags);
+        assert_eq!(vec![0], sorted(&env.it));
+        assert_eq!(vec![1], sorted(&env.frozen));
+        assert_eq!(Err(Rejection::Stuck), env.validate(1, &Action::Move(Point::new(1., 1.))));
+        assert_eq!(Ok(()), env.validate(1, &Action::Steer(Vector::new(0., 0.))));
+        assert_eq!(Err(Rejection::Frozen(1)), env.validate(0, &Tag(1)));
+        assert_eq!(None, env.winners());
+
+        // a rescue and a freeze at once, both judged from the start of the step
+        let report = env.step_all(vec![(2, Tag(1)), (0, Tag(2))], &mut rng);
+        assert_eq!((vec![(2, 1)], vec![(0, 2)]), (report.rescues, report.tags));
+        assert_eq!(vec![2], sorted(&env.frozen));
+
+        env.step_all(vec![(0, Tag(1))], &mut rng);
+        assert_eq!(Some(vec![0]), env.winners());
+        assert!(env.leaders().is_empty());
+    }
+
+    #[test]
+    fn infections_spread() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = base_env();
+        env.mode = GameMode::Infection;
+        env.step_all(vec![(0, Tag(1))], &mut rng);
+        assert_eq!(vec![0, 1], sorted(&env.it));
+        assert!(env.agents.get(&0).unwrap().is_it);
+        assert_eq!(0, env.agents.get(&1).unwrap().last_tagged);
+        assert_eq!(None, env.winners());
+
+        env.step_all(vec![(1, Tag(2))], &mut rng);
+        assert_eq!(Some(vec![2]), env.winners());
+        env.remove_agent(0);
+        assert_eq!(vec![1, 2], sorted(&env.it));
+    }
+
+    #[test]
+    fn tags_players_out() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = base_env();
+        env.mode = GameMode::Elimination;
+        let report = env.step_all(vec![(0, Tag(1)), (1, Action::Steer(Vector::new(0.5, 0.)))], &mut rng);
+        assert_eq!(vec![(0, 1)], report.tags);
+        assert_eq!(vec![Conflict::CaughtMoving { target: 1, tagger: 0 }], report.conflicts);
+        assert!(!env.agents.contains_key(&1));
+        assert_eq!(vec![0], sorted(&env.it));
+        assert_eq!(Some(vec![2]), env.winners());
+        let report = env.step_in_order(vec![(1, Action::Steer(Vector::new(0.5, 0.)))], &mut rng);
+        assert!(report.rejections.is_empty());
+    }
+
+    #[test]
+    fn teams_tag_opponents_on_their_side() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = base_env();
+        env.mode = GameMode::Teams;
+        env.it.clear();
+        env.agents.get_mut(&0).unwrap().untag();
+        env.agents.get_mut(&1).unwrap().update_team(1);
+        env.agents.get_mut(&2).unwrap().update_team(1);
+        assert_eq!(Err(Rejection::Protected(2)), env.validate(0, &Tag(2)));
+        assert_eq!(Err(Rejection::Protected(0)), env.validate(1, &Tag(0)));
+        assert_eq!(Err(Rejection::Teammate(1)), env.validate(2, &Tag(1)));
+
+        env.step_all(vec![(0, Tag(1))], &mut rng);
+        assert_eq!(vec![1], sorted(&env.frozen));
+        assert_eq!(env.jail(0), env.agents.get(&1).unwrap().position);
+        assert_eq!(None, env.winners());
+        let report = env.step_all(vec![(2, Tag(1))], &mut rng);
+        assert_eq!(vec![(2, 1)], report.rescues);
+        assert!(env.frozen.is_empty());
+
+        env.capture = Capture::Switch;
+        let report = env.step_all(vec![(0, Tag(1))], &mut rng);
+        assert_eq!(vec![(0, 1)], report.tags);
+        assert_eq!(0, env.agents.get(&1).unwrap().team);
+        assert_eq!((vec![0, 1], vec![2]), (env.team_members(0), env.team_members(1)));
+        assert_eq!(None, env.winners());
+        assert_eq!(vec![0, 1], env.leaders());
+    }
+
+    #[test]
+    fn flags_are_carried_home() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let params = TagParams { width: 100, height: 40, num_players: 0, mode: GameMode::Flag, flags_to_win: 1, ..DEFAULT_PARAMS };
+        let mut env: TagEnvironment<Player> = TagEnvironment::new(&params);
+        let mut place = |id: usize, team: usize, x: f32, speed: f32| env.add_agent(Player { team, speed, ..player(id, x, 20.) });
+        place(0, 0, 84., 5.);
+        place(1, 1, 85., 5.);
+        place(2, 0, 80., 40.);
+        place(3, 0, 86.5, 5.);
+        assert_eq!(Point::new(85., 20.), env.flags[1].position);
+        assert_eq!(Err(Rejection::OwnFlag(1)), env.validate(1, &Action::PickUp(1)));
+        assert_eq!(Err(Rejection::UnknownFlag(2)), env.validate(0, &Action::PickUp(2)));
+        assert_eq!(Err(Rejection::NotCarrying), env.validate(0, &Action::Drop));
+        assert!(matches!(env.validate(2, &Action::PickUp(1)), Err(Rejection::OutOfReach { .. })));
+
+        let report = env.step_all(vec![(0, Action::PickUp(1)), (3, Action::PickUp(1))], &mut rng);
+        assert_eq!(vec![Conflict::SharedFlag { team: 1, winner: 0, losers: vec![3] }], report.conflicts);
+        assert_eq!((Some(1), Point::new(84., 20.)), (env.carrying(0), env.flag_position(&env.flags[1])));
+        assert_eq!(Err(Rejection::FlagTaken(1)), env.validate(2, &Action::PickUp(1)));
+
+        // caught on enemy ground, the carrier drops the flag and goes to jail
+        env.step_all(vec![(1, Tag(0))], &mut rng);
+        assert_eq!((None, env.jail(1)), (env.carrying(0), env.agents.get(&0).unwrap().position));
+        assert_eq!(Point::new(84., 20.), env.flag_position(&env.flags[1]));
+
+        env.step_in_order(vec![(2, Action::Move(Point::new(83., 20.))), (2, Action::PickUp(1))], &mut rng);
+        assert_eq!(Some(1), env.carrying(2));
+        assert_eq!(None, env.winners());
+        let report = env.step_all(vec![(2, Action::Move(Point::new(45., 20.)))], &mut rng);
+        assert_eq!(vec![(2, 1)], report.captures);
+        assert_eq!(([1, 0], None), (env.scores, env.carrying(2)));
+        assert_eq!(env.flag_stand(1), env.flag_position(&env.flags[1]));
+        assert_eq!(Some(vec![0, 2, 3]), env.winners());
+    }
+
+    #[test]
+    fn queries_follow_moves() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = base_env();
+        assert_eq!(vec![0, 1], env.neighbours_within(Point::new(0., 0.), 1.).iter().map(|agent| agent.id).collect::<Vec<usize>>());
+        env.step(1, &Action::Move(Point::new(1.9, 1.9)), &mut rng).unwrap();
+        assert_eq!(vec![0], env.neighbours_within(Point::new(0., 0.), 1.).iter().map(|agent| agent.id).collect::<Vec<usize>>());
+        assert_eq!(Some(2), env.nearest_matching(Point::new(0.9, 0.9), |agent| !agent.is_it).map(|agent| agent.id));
+        assert_eq!(Some(0), env.nearest_matching(Point::new(1.9, 1.9), |agent| agent.is_it).map(|agent| agent.id));
+    }
+
     #[test]
     fn multiple_tag_same() {
+        let mut rng = StdRng::seed_from_u64(0);
         let mut env: TagEnvironment<Player> = base_env();
+        env.it.insert(1);
+        env.agents.get_mut(&1).unwrap().tag(1);
         let act = Tag(2);
-        env.step(1, &act);
+        assert_eq!(Ok(()), env.step(1, &act, &mut rng));
         assert_eq!(2, env.it.len());
-        env.step(0, &act);
+        assert_eq!(Err(Rejection::AlreadyIt(2)), env.step(0, &act, &mut rng));
         assert_eq!(2, env.it.len());
     }
 
-}
\ No newline at end of file
+    /// Agents 0 and 1 are both 'it' and both tag agent 2, which tries to move away at the same time.
+    fn contested_env(policy: ConflictPolicy) -> TagEnvironment<Player> {
+        let mut env: TagEnvironment<Player> = base_env();
+        env.conflict_policy = policy;
+        env.it.insert(1);
+        env.agents.get_mut(&1).unwrap().tag(1);
+        env
+    }
+
+    fn contested_actions() -> Vec<(usize, Action)> {
+        vec![(0, Tag(2)), (1, Tag(2)), (2, Action::Move(Point::new(2., 2.)))]
+    }
+
+    #[test]
+    fn resolves_simultaneous_tags() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env = contested_env(ConflictPolicy::ClosestTagger);
+        let report = env.step_all(contested_actions(), &mut rng);
+        assert!(report.rejections.is_empty());
+        assert_eq!(vec![
+            Conflict::SharedTarget { target: 2, winner: 1, losers: vec![0] },
+            Conflict::CaughtMoving { target: 2, tagger: 1 },
+        ], report.conflicts);
+        assert_eq!(vec![0, 2], sorted(&env.it));
+        assert_eq!(1, env.agents.get(&2).unwrap().last_tagged);
+        assert_eq!(Point::new(1., 1.), env.agents.get(&2).unwrap().position);
+
+        let mut env = contested_env(ConflictPolicy::EarliestIndex);
+        env.step_all(contested_actions(), &mut rng);
+        assert_eq!(0, env.agents.get(&2).unwrap().last_tagged);
+        assert_eq!(vec![1, 2], sorted(&env.it));
+
+        let winners: Vec<usize> = (0..20).map(|seed| {
+            let mut env = contested_env(ConflictPolicy::Random);
+            env.step_all(contested_actions(), &mut StdRng::seed_from_u64(seed));
+            let winner = env.agents.get(&2).unwrap().last_tagged;
+            winner
+        }).collect();
+        assert!(winners.contains(&0) && winners.contains(&1));
+        let mut env = contested_env(ConflictPolicy::Random);
+        env.step_all(contested_actions(), &mut StdRng::seed_from_u64(3));
+        assert_eq!(winners[3], env.agents.get(&2).unwrap().last_tagged);
+    }
+
+    #[test]
+    fn step_all_checks_against_start_of_step() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = base_env();
+        let report = env.step_all(vec![(0, Tag(1)), (1, Tag(2)), (2, Action::Move(Point::new(1.5, 1.5)))], &mut rng);
+        assert_eq!(vec![(1, Rejection::NotIt)], report.rejections);
+        assert!(report.conflicts.is_empty());
+        assert_eq!(vec![1], sorted(&env.it));
+        assert_eq!(Point::new(1.5, 1.5), env.agents.get(&2).unwrap().position);
+    }
+
+    fn sorted(it: &HashSet<usize>) -> Vec<usize> {
+        let mut it: Vec<usize> = it.iter().copied().collect();
+        it.sort_unstable();
+        it
+    }
+
+    #[test]
+    fn moves_and_tags_atomically() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = base_env();
+        env.agents.get_mut(&0).unwrap().reach = 0.1;
+        let report = env.step_all(vec![(0, Action::MoveAndTag(Point::new(1.4, 1.4), 1)), (1, Action::Move(Point::new(0.5, 0.6))), (2, Action::Move(Point::new(1., 1.)))], &mut rng);
+        assert!(matches!(report.rejections[..], [(0, Rejection::OutOfReach { .. })]));
+        assert_eq!(Point::new(0., 0.), env.agents.get(&0).unwrap().position);
+        assert!(!env.agents.get(&1).unwrap().is_it);
+
+        let report = env.step_all(vec![(0, Action::MoveAndTag(Point::new(0.5, 0.55), 1)), (1, Action::Move(Point::new(0.5, 0.7))), (2, Action::Move(Point::new(1., 1.)))], &mut rng);
+        assert_eq!(vec![Conflict::CaughtMoving { target: 1, tagger: 0 }], report.conflicts);
+        assert_eq!(Point::new(0.5, 0.55), env.agents.get(&0).unwrap().position);
+        assert_eq!(Point::new(0.5, 0.6), env.agents.get(&1).unwrap().position);
+        assert_eq!(vec![1], sorted(&env.it));
+        assert_eq!(Ok(()), env.step(1, &Action::MoveAndTag(Point::new(0.5, 2.), 2), &mut rng));
+        assert_eq!(Point::new(0.5, 2.), env.agents.get(&1).unwrap().position);
+        assert_eq!(vec![2], sorted(&env.it));
+    }
+
+    #[test]
+    fn rejects_invalid_actions() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = base_env();
+        assert!(matches!(env.step(1, &Action::Move(Point::new(2., 2.)), &mut rng), Err(Rejection::TooFar { .. })));
+        assert_eq!(Err(Rejection::OutOfBounds(Point::new(-0.5, 0.5))), env.step(1, &Action::Move(Point::new(-0.5, 0.5)), &mut rng));
+        assert_eq!(Err(Rejection::NotIt), env.step(1, &Tag(2), &mut rng));
+        assert_eq!(Err(Rejection::UnknownTarget(9)), env.step(0, &Tag(9), &mut rng));
+        assert_eq!(Err(Rejection::UnknownAgent(9)), env.step(9, &Tag(1), &mut rng));
+        assert_eq!(Err(Rejection::SelfTag), env.step(0, &Tag(0), &mut rng));
+        env.agents.get_mut(&0).unwrap().reach = 1.0;
+        assert!(matches!(env.step(0, &Tag(2), &mut rng), Err(Rejection::OutOfReach { .. })));
+        env.agents.get_mut(&0).unwrap().tag(2);
+        assert_eq!(Err(Rejection::TagBack(2)), env.step(0, &Tag(2), &mut rng));
+        assert_eq!(Point::new(0.5, 0.5), env.agents.get(&1).unwrap().position);
+        assert_eq!(3, env.violations(1));
+        assert_eq!(4, env.violations(0));
+        assert_eq!(1, env.violations(9));
+        assert_eq!(Ok(()), env.step(0, &Tag(1), &mut rng));
+        assert_eq!(Ok(()), env.step(1, &Action::Move(Point::new(1.5, 1.5)), &mut rng));
+        assert_eq!(4, env.violations(0));
+    }
+
+    #[test]
+    fn applies_tag_rules() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = base_env();
+        env.rules = TagRules { tag_back_cooldown: 2, immunity: 2, ..DEFAULT_RULES };
+        env.step_all(vec![(0, Tag(1)), (1, Action::Move(Point::new(0.5, 0.5))), (2, Action::Move(Point::new(1., 1.)))], &mut rng);
+        assert_eq!(Err(Rejection::TagBack(0)), env.validate(1, &Tag(0)));
+        env.rules.tag_back_cooldown = 0;
+        assert_eq!(Err(Rejection::Immune(0)), env.validate(1, &Tag(0)));
+        assert!(!env.can_tag(env.agents.get(&1).unwrap().player(), env.agents.get(&0).unwrap().player()));
+        env.steps = 2;
+        assert_eq!(Ok(()), env.validate(1, &Tag(0)));
+
+        env.rules = TagRules { miss_near: 1., miss_far: 1., ..DEFAULT_RULES };
+        let report = env.step_all(vec![(0, Action::Move(Point::new(0., 0.))), (1, Tag(2)), (2, Action::Move(Point::new(1., 1.)))], &mut rng);
+        assert_eq!(vec![(1, 2)], report.misses);
+        assert_eq!(vec![1], sorted(&env.it));
+
+        env.rules = DEFAULT_RULES;
+        env.agents.get_mut(&1).unwrap().reach = 0.5;
+        assert!(matches!(env.validate(1, &Tag(2)), Err(Rejection::OutOfReach { .. })));
+        env.rules.hit_test = HitTest::Overlap;
+        assert_eq!(Ok(()), env.validate(1, &Tag(2)));
+    }
+
+    #[test]
+    fn applies_boundary_policies() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env = bounded_env(Boundary::Wrap);
+        assert_eq!(Ok(()), env.step(2, &Action::Move(Point::new(2.5, 1.)), &mut rng));
+        assert_eq!(Point::new(0.5, 1.), env.agents.get(&2).unwrap().position);
+        assert!(env.neighbours_within(Point::new(1.9, 0.), 0.2).iter().any(|agent| agent.id == 0));
+
+        let mut env = bounded_env(Boundary::Reflect);
+        assert_eq!(Ok(()), env.step(2, &Action::Move(Point::new(2.5, 1.)), &mut rng));
+        assert_eq!(Point::new(1.5, 1.), env.agents.get(&2).unwrap().position);
+
+        let mut env = bounded_env(Boundary::Eliminate);
+        let report = env.step_all(vec![(0, Action::Move(Point::new(-1., 0.))), (1, Action::Move(Point::new(0.5, 0.5))), (2, Action::Move(Point::new(1., 1.)))], &mut rng);
+        assert_eq!(vec![0], report.eliminated);
+        assert!(env.agents.get(&0).is_none());
+        assert_eq!(vec![1], sorted(&env.it));
+        assert!(env.agents.get(&1).unwrap().is_it);
+    }
+
+    #[test]
+    fn obstacles_block_moves_and_tags() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = base_env();
+        env.obstacles = vec![Obstacle::Polygon(vec![Point::new(0.7, 0.6), Point::new(0.8, 0.6), Point::new(0.8, 2.), Point::new(0.7, 2.)])];
+        assert_eq!(Err(Rejection::OutOfSight(2)), env.validate(0, &Tag(2)));
+        assert_eq!(Ok(()), env.validate(0, &Tag(1)));
+        assert!(!env.within_reach(env.agents.get(&0).unwrap().player(), Point::new(0., 0.), env.agents.get(&2).unwrap().player()));
+
+        assert_eq!(Ok(()), env.step(2, &Action::Move(Point::new(0.5, 1.)), &mut rng));
+        let position = env.agents.get(&2).unwrap().position;
+        assert!(position.x > 0.8 && position.x < 0.82 && position.y == 1., "{:?}", position);
+
+        let detour = env.steer(position, Point::new(0.5, 1.));
+        assert!(env.in_sight(position, detour) && detour != position);
+    }
+
+    #[test]
+    fn keeps_bodies_apart() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let with_bodies = |collisions: Collisions| {
+            let mut env: TagEnvironment<Player> = base_env();
+            env.collisions = collisions;
+            for agent in 0..3 {
+                let mut player = *env.agents.get(&agent).unwrap();
+                player.radius = 0.25;
+                env.add_agent(player);
+            }
+            env
+        };
+        let gap = |env: &TagEnvironment<Player>, first: usize, second: usize| {
+            env.agents.get(&first).unwrap().position.distance(env.agents.get(&second).unwrap().position)
+        };
+
+        let mut env = with_bodies(Collisions::Push);
+        assert_eq!(Ok(()), env.step(2, &Action::Move(Point::new(0.5, 0.5)), &mut rng));
+        assert_eq!(Point::new(0.25, 0.5), env.agents.get(&1).unwrap().position);
+        assert_eq!(Point::new(0.75, 0.5), env.agents.get(&2).unwrap().position);
+        assert!(gap(&env, 0, 1) >= 0.5 && gap(&env, 0, 2) >= 0.5);
+
+        let mut env = with_bodies(Collisions::Block);
+        assert_eq!(Ok(()), env.step(2, &Action::Move(Point::new(0.5, 0.5)), &mut rng));
+        assert_eq!(Point::new(0.5, 0.5), env.agents.get(&1).unwrap().position);
+        assert!(gap(&env, 1, 2) >= 0.5 && gap(&env, 1, 2) < 0.52, "{:?}", gap(&env, 1, 2));
+    }
+
+    #[test]
+    fn integrates_motion_models() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env = base_env();
+        assert_eq!(Err(Rejection::TooFar { distance: 3., speed: 2. }), env.step(1, &Action::Steer(Vector::new(3., 0.)), &mut rng));
+
+        env.motion = Motion { model: MotionModel::Momentum, acceleration: 0.5, ..DEFAULT_MOTION };
+        assert_eq!(Ok(()), env.step(1, &Action::Steer(Vector::new(1., 0.)), &mut rng));
+        assert_eq!(Point::new(1., 0.5), env.agents.get(&1).unwrap().position);
+        assert_eq!(Ok(()), env.step(1, &Action::Steer(Vector::new(1., 0.)), &mut rng));
+        assert_eq!(Point::new(2., 0.5), env.agents.get(&1).unwrap().position);
+        assert_eq!(Vector::new(1., 0.), env.agents.get(&1).unwrap().velocity);
+
+        let mut env = base_env();
+        env.motion = Motion { model: MotionModel::Dubins, turning_radius: 1., ..DEFAULT_MOTION };
+        assert_eq!(Ok(()), env.step(1, &Action::Move(Point::new(0.5, 1.5)), &mut rng));
+        let player = env.agents.get(&1).unwrap();
+        assert!((player.heading - 1.).abs() < 1e-6);
+        assert!(player.position.distance(Point::new(0.5 + 1f32.cos(), 0.5 + 1f32.sin())) < 1e-6);
+    }
+
+    #[test]
+    fn momentum_bounces_off_reflecting_walls() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env = bounded_env(Boundary::Reflect);
+        env.motion = Motion { model: MotionModel::Momentum, acceleration: 0.5, ..DEFAULT_MOTION };
+        for _ in 0..3 {
+            env.step(1, &Action::Steer(Vector::new(1., 0.)), &mut rng).unwrap();
+        }
+        // from x = 2, on the right-hand edge, a step at full speed goes one past it and bounces back
+        let player = env.agents.get(&1).unwrap().player();
+        assert_eq!((Point::new(1., 0.5), Vector::new(-1., 0.)), (player.position, player.velocity));
+        env.step(1, &Action::Steer(Vector::new(1., 0.)), &mut rng).unwrap();
+        assert_eq!(Point::new(0.5, 0.5), env.agents.get(&1).unwrap().position);
+    }
+
+
+    #[test]
+    fn spends_and_recovers_stamina() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env = base_env();
+        env.recovery = 0.25;
+        let mut player = *env.agents.get(&1).unwrap();
+        player.sprint_speed = 3.;
+        player.stamina = 0.5;
+        player.max_stamina = 1.;
+        env.add_agent(player);
+        assert_eq!(Err(Rejection::TooFar { distance: 4., speed: 3. }), env.step(1, &Action::Sprint(Vector::new(4., 0.)), &mut rng));
+        assert_eq!(2.5, env.sprint_speed(player));
+
+        assert_eq!(Ok(()), env.step(1, &Action::Sprint(Vector::new(-3., 0.)), &mut rng));
+        assert_eq!(Point::new(0., 0.5), env.agents.get(&1).unwrap().position);
+        assert_eq!(Some((0., 1.)), env.stamina(1));
+        assert_eq!(Ok(()), env.step(1, &Action::Steer(Vector::new(0., 0.)), &mut rng));
+        assert_eq!(Some((0.25, 1.)), env.stamina(1));
+    }
+
+
+    #[test]
+    fn tags_at_the_moment_of_contact() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut env: TagEnvironment<Player> = TagEnvironment::new(&TagParams { width: 100, height: 100, ..DEFAULT_PARAMS });
+        let mut it = *base_env().agents.get(&0).unwrap();
+        it.position = Point::new(10., 50.);
+        let mut runner = *base_env().agents.get(&1).unwrap();
+        runner.position = Point::new(20., 50.);
+        env.add_agent(it);
+        env.add_agent(runner);
+        let mut oncoming = *base_env().agents.get(&2).unwrap();
+        oncoming.position = Point::new(33., 50.);
+        env.add_agent(oncoming);
+
+        // 0 catches up with 1 at time 8, at x = 26 and 28, and 1 stops there while 0 runs on; 2, coming the other way,
+        // is at x = 29 by then, so 1 catches them right away and they stop there too, and catch 0 at x = 27 at time 8.5
+        let report = env.advance(vec![(0, Action::Steer(Vector::new(2., 0.))), (1, Action::Steer(Vector::new(1., 0.))), (2, Action::Steer(Vector::new(-0.5, 0.)))], 10., &mut rng);
+        assert_eq!(vec![(0, 1), (1, 2), (2, 0)], report.tags);
+        assert_eq!(10., report.elapsed);
+        assert!(env.agents.get(&0).unwrap().position.distance(Point::new(27., 50.)) < 1e-3);
+        assert!(env.agents.get(&1).unwrap().position.distance(Point::new(28., 50.)) < 1e-3);
+        assert!(env.agents.get(&2).unwrap().position.distance(Point::new(29., 50.)) < 1e-3);
+        assert_eq!(10., env.time);
+    }
+
+}